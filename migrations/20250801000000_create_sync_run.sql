@@ -0,0 +1,18 @@
+-- 同步运行记录，每次同步（手动或定时）一行
+CREATE TABLE IF NOT EXISTS sync_run (
+    id INTEGER GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    source VARCHAR(255) NOT NULL,
+    triggered_by VARCHAR(32) NOT NULL,
+    status VARCHAR(32) NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMPTZ,
+    players_created INTEGER NOT NULL DEFAULT 0,
+    players_renamed INTEGER NOT NULL DEFAULT 0,
+    games_saved INTEGER NOT NULL DEFAULT 0,
+    games_updated INTEGER NOT NULL DEFAULT 0,
+    results_written INTEGER NOT NULL DEFAULT 0,
+    warnings TEXT[] NOT NULL DEFAULT '{}',
+    error TEXT
+);
+
+CREATE INDEX IF NOT EXISTS sync_run_started_at_idx ON sync_run (started_at DESC);
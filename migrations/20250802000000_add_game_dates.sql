@@ -0,0 +1,8 @@
+-- 比赛日期和登记时间，分别来自上游的 played 和 registered
+ALTER TABLE meetup_league_table ADD COLUMN IF NOT EXISTS played_date DATE;
+ALTER TABLE meetup_league_table ADD COLUMN IF NOT EXISTS registered TIMESTAMP;
+
+-- 已有记录从 game_time 回填
+UPDATE meetup_league_table
+SET played_date = game_time::date, registered = game_time
+WHERE played_date IS NULL AND game_time IS NOT NULL;
@@ -0,0 +1,13 @@
+-- 上游的聚会场次，ID即上游的 sid
+CREATE TABLE IF NOT EXISTS meetup_league_session (
+    id INTEGER PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    group_name VARCHAR NOT NULL,
+    date DATE
+);
+
+-- 比赛桌所属的场次
+ALTER TABLE meetup_league_table ADD COLUMN IF NOT EXISTS session_id INTEGER
+    REFERENCES meetup_league_session (id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS meetup_league_table_session_id_idx ON meetup_league_table (session_id);
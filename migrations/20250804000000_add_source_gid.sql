@@ -0,0 +1,5 @@
+-- 上游的比赛ID和原始描述，同步时按 source_gid 匹配已有记录
+ALTER TABLE meetup_league_table ADD COLUMN IF NOT EXISTS source_gid INTEGER;
+ALTER TABLE meetup_league_table ADD COLUMN IF NOT EXISTS description VARCHAR;
+
+CREATE UNIQUE INDEX IF NOT EXISTS meetup_league_table_source_gid_idx ON meetup_league_table (source_gid);
@@ -0,0 +1,9 @@
+-- 上游已删除的比赛桌和成绩：软删除时间和待审核标记
+ALTER TABLE meetup_league_table ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+ALTER TABLE meetup_league_table ADD COLUMN IF NOT EXISTS needs_review BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE meetup_league_result ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+ALTER TABLE meetup_league_result ADD COLUMN IF NOT EXISTS needs_review BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- 每次同步处理的上游缺失记录数
+ALTER TABLE sync_run ADD COLUMN IF NOT EXISTS orphan_tables INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE sync_run ADD COLUMN IF NOT EXISTS orphan_results INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,15 @@
+-- 未通过校验的上游比赛，放行前同步不会写入
+CREATE TABLE IF NOT EXISTS sync_quarantine (
+    id INTEGER GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    source_gid INTEGER NOT NULL UNIQUE,
+    description VARCHAR NOT NULL,
+    reasons TEXT[] NOT NULL DEFAULT '{}',
+    -- 上游原始比赛数据
+    payload JSONB NOT NULL,
+    first_seen TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_seen TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- 放行后该gid不再校验
+    released_at TIMESTAMPTZ
+);
+
+ALTER TABLE sync_run ADD COLUMN IF NOT EXISTS games_quarantined INTEGER NOT NULL DEFAULT 0;
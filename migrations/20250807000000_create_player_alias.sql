@@ -0,0 +1,13 @@
+-- 玩家曾用名，按名称查询玩家时当前名称找不到再查这里
+CREATE TABLE IF NOT EXISTS meetup_league_player_alias (
+    id INTEGER GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    player_id INTEGER NOT NULL REFERENCES meetup_league_player(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    -- 合并而来时记录被合并玩家的ID，同步时上游该pid的成绩归入 player_id
+    merged_from INTEGER,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_meetup_league_player_alias_name ON meetup_league_player_alias (name);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_meetup_league_player_alias_merged_from
+    ON meetup_league_player_alias (merged_from) WHERE merged_from IS NOT NULL;
@@ -0,0 +1,18 @@
+-- 数据源每个不同内容的原始 data.json，gzip压缩，按内容的SHA-256哈希区分
+CREATE TABLE IF NOT EXISTS sync_snapshot (
+    id INTEGER GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    source VARCHAR(255) NOT NULL,
+    content_hash CHAR(64) NOT NULL,
+    -- 响应头，下次拉取时用于条件请求
+    etag VARCHAR,
+    last_modified VARCHAR,
+    size_bytes INTEGER NOT NULL,
+    data BYTEA NOT NULL,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_seen TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- 最近一次由同步成功导入的时间，内容未变化时据此跳过同步
+    imported_at TIMESTAMPTZ,
+    UNIQUE (source, content_hash)
+);
+
+ALTER TABLE sync_run ADD COLUMN IF NOT EXISTS snapshot_id INTEGER REFERENCES sync_snapshot(id) ON DELETE SET NULL;
@@ -0,0 +1,2 @@
+-- 失败分类：network、http_status、schema、payload_too_large、config 或 database
+ALTER TABLE sync_run ADD COLUMN IF NOT EXISTS error_kind VARCHAR(32);
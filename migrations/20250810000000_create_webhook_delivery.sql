@@ -0,0 +1,18 @@
+-- 同步完成后发出的webhook及其投递结果
+CREATE TABLE IF NOT EXISTS webhook_delivery (
+    id INTEGER GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    webhook VARCHAR(255) NOT NULL,
+    url VARCHAR NOT NULL,
+    event VARCHAR(64) NOT NULL,
+    run_id INTEGER REFERENCES sync_run(id) ON DELETE SET NULL,
+    payload JSONB NOT NULL,
+    -- pending（投递中）、delivered 或 failed（重试用尽）
+    status VARCHAR(32) NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    response_status INTEGER,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    delivered_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_delivery_created_at ON webhook_delivery (created_at DESC);
@@ -0,0 +1,9 @@
+-- 从描述中解析出的分组或阶段，例如 Group A、Finals
+ALTER TABLE meetup_league_table ADD COLUMN IF NOT EXISTS group_name VARCHAR(255);
+
+-- 按原始描述回填已有记录：Season X: <分组>: Table Y
+UPDATE meetup_league_table
+SET group_name = substring(description FROM '^Season \d+: (.+): Table \d+$')
+WHERE group_name IS NULL AND description IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS meetup_league_table_season_group_idx ON meetup_league_table (season_num, group_name);
@@ -0,0 +1,46 @@
+use crate::config::ReconcileMode;
+use crate::db::LeagueRepository;
+use crate::sync::source::{DataSource, FileSource};
+use crate::sync::{self, diff, SyncTrigger};
+
+const IMPORT_USAGE: &str = "用法: ankan-meetup-analyser-server import <data.json路径> [--dry-run]";
+
+// 命令行导入本地的 data.json，与 /sync/upload 使用同样的导入流程
+// 状态锁只在本进程内有效，导入时服务器不应同时在同步
+pub async fn import(repo: &LeagueRepository, args: &[String]) -> Result<(), String> {
+    let mut path = None;
+    let mut dry_run = false;
+    for arg in args {
+        match arg.as_str() {
+            "--dry-run" => dry_run = true,
+            other if path.is_none() && !other.starts_with("--") => path = Some(other),
+            other => return Err(format!("无法识别的参数 {}\n{}", other, IMPORT_USAGE)),
+        }
+    }
+    let path = path.ok_or(IMPORT_USAGE)?;
+
+    let source = FileSource::new(path);
+    if dry_run {
+        let data = source.load().await.map_err(|e| e.to_string())?;
+        // 只读对比，事务最后回滚
+        let mut tx = repo.begin().await.map_err(|e| format!("开启事务失败: {}", e))?;
+        let diff = diff::build_diff(&mut tx, &data, ReconcileMode::Off).await;
+        let _ = tx.rollback().await;
+        let diff = diff.map_err(|e| format!("生成同步差异失败: {}", e))?;
+        println!("{} 导入预览，共 {} 场比赛", source.label(), data.collection.games.len());
+        print!("{}", diff.to_text());
+        return Ok(());
+    }
+
+    let summary = sync::start_import(repo, source, SyncTrigger::Cli)
+        .await
+        .map_err(|e| e.to_string())?
+        .run_and_wait_webhooks()
+        .await
+        .map_err(|e| e.to_string())?;
+    for warning in &summary.warnings {
+        println!("警告: {}", warning);
+    }
+    println!("{}", summary.message());
+    Ok(())
+}
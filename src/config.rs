@@ -0,0 +1,362 @@
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::str::FromStr;
+
+// 内置默认数据源：斯德哥尔摩麻将聚会联赛
+pub const DEFAULT_SOURCE_NAME: &str = "sthlm";
+pub const DEFAULT_SOURCE_URL: &str = "https://mahjong.chaotic.quest/sthlm-meetups-league/data.json";
+
+// 单个命名数据源
+#[derive(Debug, Clone, Deserialize)]
+pub struct SourceConfig {
+    pub name: String,
+    pub url: String,
+    // 单场比赛页面地址模板，{gid} 会替换为比赛ID，html/backfill 模式需要
+    #[serde(default)]
+    pub game_page_url: Option<String>,
+    // 数据源所属的联赛，未填写时视为默认联赛
+    #[serde(default)]
+    pub league: Option<String>,
+}
+
+// 定时同步配置，interval_secs 与 cron 二选一
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ScheduleConfig {
+    #[serde(default)]
+    pub interval_secs: Option<u64>,
+    // 带秒字段的cron表达式，例如 "0 0 3 * * *"，按UTC计算
+    #[serde(default)]
+    pub cron: Option<String>,
+    // 定时同步使用的数据源，未指定时使用默认数据源
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+// 拉取数据源和比赛页面的HTTP策略
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FetchConfig {
+    pub connect_timeout_secs: u64,
+    // 两次收到数据之间的最长等待
+    pub read_timeout_secs: u64,
+    // 网络错误、429和5xx时的重试次数
+    pub retries: u32,
+    // 第一次重试前的等待，之后每次翻倍并加随机抖动，不超过 max_backoff_ms
+    pub backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    // 响应体大小上限
+    pub max_payload_bytes: u64,
+}
+
+impl Default for FetchConfig {
+    fn default() -> Self {
+        FetchConfig {
+            connect_timeout_secs: 10,
+            read_timeout_secs: 30,
+            retries: 3,
+            backoff_ms: 500,
+            max_backoff_ms: 10_000,
+            max_payload_bytes: 50 * 1024 * 1024,
+        }
+    }
+}
+
+// 同步完成后通知的地址
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookConfig {
+    pub name: String,
+    pub url: String,
+    // 设置后请求带 X-Ankan-Signature-256: sha256=<HMAC-SHA256(secret, 请求体)>
+    #[serde(default)]
+    pub secret: Option<String>,
+    // 投递失败后的重试次数
+    #[serde(default = "default_webhook_retries")]
+    pub retries: u32,
+}
+
+fn default_webhook_retries() -> u32 {
+    3
+}
+
+// 上游已不存在的比赛桌和成绩的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReconcileMode {
+    // 不处理
+    #[default]
+    Off,
+    // 标记为待审核
+    Flag,
+    // 记录删除时间，查询时不再返回
+    SoftDelete,
+    // 从数据库删除
+    Delete,
+}
+
+impl ReconcileMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReconcileMode::Off => "off",
+            ReconcileMode::Flag => "flag",
+            ReconcileMode::SoftDelete => "soft_delete",
+            ReconcileMode::Delete => "delete",
+        }
+    }
+}
+
+impl FromStr for ReconcileMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "off" => Ok(ReconcileMode::Off),
+            "flag" => Ok(ReconcileMode::Flag),
+            "soft_delete" => Ok(ReconcileMode::SoftDelete),
+            "delete" => Ok(ReconcileMode::Delete),
+            other => Err(format!("未知的对账模式 {}，可选 off、flag、soft_delete、delete", other)),
+        }
+    }
+}
+
+// 同步配置，可来自JSON配置文件或环境变量
+#[derive(Debug, Clone, Deserialize)]
+pub struct SyncConfig {
+    #[serde(default)]
+    pub default_source: Option<String>,
+    #[serde(default)]
+    pub sources: Vec<SourceConfig>,
+    #[serde(default)]
+    pub schedule: ScheduleConfig,
+    #[serde(default)]
+    pub reconcile: ReconcileMode,
+    #[serde(default)]
+    pub fetch: FetchConfig,
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+}
+
+impl SyncConfig {
+    // 加载顺序：SYNC_CONFIG 指定的配置文件 -> SYNC_SOURCES -> SYNC_SOURCE_URL -> 内置默认数据源
+    // SYNC_DEFAULT_SOURCE 可覆盖默认数据源名称
+    pub fn load() -> Result<Self, String> {
+        let mut config = match env::var("SYNC_CONFIG") {
+            Ok(path) => {
+                let content = fs::read_to_string(&path)
+                    .map_err(|e| format!("读取同步配置文件 {} 失败: {}", path, e))?;
+                serde_json::from_str::<SyncConfig>(&content)
+                    .map_err(|e| format!("解析同步配置文件 {} 失败: {}", path, e))?
+            }
+            Err(_) => SyncConfig {
+                default_source: None,
+                sources: Vec::new(),
+                schedule: ScheduleConfig::default(),
+                reconcile: ReconcileMode::default(),
+                fetch: FetchConfig::default(),
+                webhooks: Vec::new(),
+            },
+        };
+
+        // 格式: name=url,name2=url2
+        if let Ok(sources) = env::var("SYNC_SOURCES") {
+            for entry in sources.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                let (name, url) = entry
+                    .split_once('=')
+                    .ok_or_else(|| format!("SYNC_SOURCES 格式错误，应为 name=url: {}", entry))?;
+                config.set_source(name.trim(), url.trim());
+            }
+        }
+
+        if let Ok(url) = env::var("SYNC_SOURCE_URL") {
+            config.set_source(DEFAULT_SOURCE_NAME, url.trim());
+        }
+
+        if config.sources.is_empty() {
+            config.set_source(DEFAULT_SOURCE_NAME, DEFAULT_SOURCE_URL);
+        }
+
+        // 格式: name=模板,name2=模板2，例如 sthlm=https://example.com/game/{gid}
+        if let Ok(pages) = env::var("SYNC_GAME_PAGE_URLS") {
+            for entry in pages.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                let (name, template) = entry
+                    .split_once('=')
+                    .ok_or_else(|| format!("SYNC_GAME_PAGE_URLS 格式错误，应为 name=模板: {}", entry))?;
+                let source = config
+                    .sources
+                    .iter_mut()
+                    .find(|s| s.name == name.trim())
+                    .ok_or_else(|| format!("SYNC_GAME_PAGE_URLS 中的数据源 {} 未在配置中定义", name.trim()))?;
+                source.game_page_url = Some(template.trim().to_string());
+            }
+        }
+        for source in &config.sources {
+            if let Some(template) = &source.game_page_url
+                && !template.contains("{gid}")
+            {
+                return Err(format!("数据源 {} 的比赛页面地址模板缺少 {{gid}}: {}", source.name, template));
+            }
+        }
+
+        config.validate_league()?;
+
+        if let Ok(name) = env::var("SYNC_DEFAULT_SOURCE") {
+            config.default_source = Some(name);
+        }
+
+        if let Some(name) = &config.default_source
+            && !config.sources.iter().any(|s| &s.name == name)
+        {
+            return Err(format!("默认数据源 {} 未在配置中定义", name));
+        }
+
+        if let Some(secs) = env_number("SYNC_INTERVAL_SECS")? {
+            config.schedule.interval_secs = Some(secs);
+        }
+        if let Ok(cron) = env::var("SYNC_CRON") {
+            config.schedule.cron = Some(cron);
+        }
+        if let Ok(name) = env::var("SYNC_SCHEDULE_SOURCE") {
+            config.schedule.source = Some(name);
+        }
+        config.validate_schedule()?;
+
+        if let Ok(mode) = env::var("SYNC_RECONCILE") {
+            config.reconcile = mode.parse()?;
+        }
+
+        let fetch = &mut config.fetch;
+        if let Some(secs) = env_number("SYNC_CONNECT_TIMEOUT_SECS")? {
+            fetch.connect_timeout_secs = secs;
+        }
+        if let Some(secs) = env_number("SYNC_READ_TIMEOUT_SECS")? {
+            fetch.read_timeout_secs = secs;
+        }
+        if let Some(retries) = env_number("SYNC_RETRIES")? {
+            fetch.retries = retries;
+        }
+        if let Some(ms) = env_number("SYNC_RETRY_BACKOFF_MS")? {
+            fetch.backoff_ms = ms;
+        }
+        if let Some(ms) = env_number("SYNC_MAX_BACKOFF_MS")? {
+            fetch.max_backoff_ms = ms;
+        }
+        if let Some(bytes) = env_number("SYNC_MAX_PAYLOAD_BYTES")? {
+            fetch.max_payload_bytes = bytes;
+        }
+        config.validate_fetch()?;
+
+        // 格式: name=url,name2=url2，同名的覆盖配置文件中的地址
+        if let Ok(hooks) = env::var("SYNC_WEBHOOKS") {
+            for entry in hooks.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                let (name, url) = entry
+                    .split_once('=')
+                    .ok_or_else(|| format!("SYNC_WEBHOOKS 格式错误，应为 name=url: {}", entry))?;
+                match config.webhooks.iter_mut().find(|h| h.name == name.trim()) {
+                    Some(hook) => hook.url = url.trim().to_string(),
+                    None => config.webhooks.push(WebhookConfig {
+                        name: name.trim().to_string(),
+                        url: url.trim().to_string(),
+                        secret: None,
+                        retries: default_webhook_retries(),
+                    }),
+                }
+            }
+        }
+        // 未单独设置密钥的webhook使用 SYNC_WEBHOOK_SECRET
+        if let Ok(secret) = env::var("SYNC_WEBHOOK_SECRET") {
+            for hook in config.webhooks.iter_mut().filter(|h| h.secret.is_none()) {
+                hook.secret = Some(secret.clone());
+            }
+        }
+
+        Ok(config)
+    }
+
+    // 按名称查找数据源，未指定名称时返回默认数据源
+    pub fn source(&self, name: Option<&str>) -> Option<&SourceConfig> {
+        match name.or(self.default_source.as_deref()) {
+            Some(name) => self.sources.iter().find(|s| s.name == name),
+            None => self.sources.first(),
+        }
+    }
+
+    // 上游的比赛gid、玩家pid和场次sid直接用作本地键（比赛桌和隔离区按gid唯一），
+    // 一个数据库只能同步一个联赛，多个数据源只能是同一联赛的镜像或新地址
+    fn validate_league(&self) -> Result<(), String> {
+        let Some(first) = self.sources.first() else {
+            return Ok(());
+        };
+        match self.sources.iter().find(|s| s.league != first.league) {
+            Some(other) => Err(format!(
+                "数据源 {} 和 {} 属于不同联赛（{} / {}），比赛gid、玩家pid和场次sid会互相覆盖，一个数据库只能同步一个联赛",
+                first.name,
+                other.name,
+                first.league.as_deref().unwrap_or("默认"),
+                other.league.as_deref().unwrap_or("默认")
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn validate_schedule(&self) -> Result<(), String> {
+        let schedule = &self.schedule;
+        if schedule.interval_secs.is_some() && schedule.cron.is_some() {
+            return Err("定时同步的 interval_secs 与 cron 只能配置一个".to_string());
+        }
+        if schedule.interval_secs == Some(0) {
+            return Err("定时同步间隔必须大于0秒".to_string());
+        }
+        if let Some(expr) = &schedule.cron {
+            cron::Schedule::from_str(expr).map_err(|e| format!("cron表达式 {} 无效: {}", expr, e))?;
+        }
+        if let Some(name) = &schedule.source
+            && self.source(Some(name)).is_none()
+        {
+            return Err(format!("定时同步数据源 {} 未在配置中定义", name));
+        }
+        Ok(())
+    }
+
+    fn validate_fetch(&self) -> Result<(), String> {
+        let fetch = &self.fetch;
+        if fetch.connect_timeout_secs == 0 || fetch.read_timeout_secs == 0 {
+            return Err("拉取的连接和读取超时必须大于0秒".to_string());
+        }
+        if fetch.max_payload_bytes == 0 {
+            return Err("响应体大小上限必须大于0".to_string());
+        }
+        Ok(())
+    }
+
+    // 同名数据源后定义的覆盖先定义的
+    fn set_source(&mut self, name: &str, url: &str) {
+        match self.sources.iter_mut().find(|s| s.name == name) {
+            Some(source) => source.url = url.to_string(),
+            None => self.sources.push(SourceConfig {
+                name: name.to_string(),
+                url: url.to_string(),
+                game_page_url: None,
+                league: None,
+            }),
+        }
+    }
+}
+
+// 读取数字类型的环境变量，未设置时返回 None
+fn env_number<T: FromStr>(name: &str) -> Result<Option<T>, String>
+where
+    T::Err: std::fmt::Display,
+{
+    match env::var(name) {
+        Ok(value) => value
+            .trim()
+            .parse::<T>()
+            .map(Some)
+            .map_err(|e| format!("{} 格式错误: {}", name, e)),
+        Err(_) => Ok(None),
+    }
+}
+
+lazy_static::lazy_static! {
+    pub static ref SYNC_CONFIG: SyncConfig = SyncConfig::load().expect("同步配置加载失败");
+}
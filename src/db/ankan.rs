@@ -1,11 +1,12 @@
 // src/db/league_db.rs
 
-use sqlx::{PgPool, Error, postgres::PgQueryResult};
-use crate::models::league::{LeaguePlayer, LeagueGame, LeagueResult};
+use sqlx::{PgPool, Postgres, Transaction, Error, postgres::PgQueryResult};
+use crate::models::league::{GameInfo, LeaguePlayer, LeagueGame, LeagueResult, PlayerResult};
+use std::collections::HashMap;
 
 #[derive(Clone)]
 pub struct LeagueRepository {
-    pool: PgPool,
+    pub(super) pool: PgPool,
 }
 
 impl LeagueRepository {
@@ -13,191 +14,304 @@ impl LeagueRepository {
         Self { pool }
     }
 
-    // LeaguePlayer CRUD 操作
-    pub async fn create_player(&self, player: &LeaguePlayer) -> Result<i32, Error> {
-        sqlx::query_scalar!(
-        "INSERT INTO meetup_league_player (name) VALUES ($1) RETURNING id",
-        player.name
-    )
-            .fetch_one(&self.pool)
-            .await
+    // 开启事务，返回事务作用域的仓库操作
+    pub async fn begin(&self) -> Result<LeagueTransaction, Error> {
+        Ok(LeagueTransaction {
+            tx: self.pool.begin().await?,
+        })
     }
 
-    // 添加指定ID创建玩家的方法
-    pub async fn create_player_with_id(&self, player: &LeaguePlayer) -> Result<i32, Error> {
-        // 使用OVERRIDING SYSTEM VALUE插入指定ID
-        sqlx::query_scalar!(
+    // 添加通过名称查询玩家的功能，当前名称优先，其次按最近记录的曾用名
+    pub async fn get_player_by_name(&self, name: &str) -> Result<LeaguePlayer, Error> {
+        sqlx::query_as!(
+            LeaguePlayer,
             r#"
-            INSERT INTO meetup_league_player (id, name)
-            OVERRIDING SYSTEM VALUE
-            VALUES ($1, $2)
-            RETURNING id
+            SELECT p.id, p.name FROM meetup_league_player p
+            LEFT JOIN meetup_league_player_alias a ON a.player_id = p.id AND a.name = $1
+            WHERE p.name = $1 OR a.id IS NOT NULL
+            ORDER BY (p.name = $1) DESC, a.created_at DESC
+            LIMIT 1
             "#,
-            player.id,
-            player.name
+            name
         )
-        .fetch_one(&self.pool)
-        .await
+            .fetch_one(&self.pool)
+            .await
     }
 
-    pub async fn get_player(&self, id: i32) -> Result<LeaguePlayer, Error> {
+    pub async fn list_players(&self) -> Result<Vec<LeaguePlayer>, Error> {
+        sqlx::query_as!(LeaguePlayer, "SELECT id, name FROM meetup_league_player")
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    // 获取指定玩家的所有对战数据（GameInfo）
+    pub async fn get_player_matches(&self, player_id: i32) -> Result<Vec<GameInfo>, Error> {
+        self.player_matches(player_id, None).await
+    }
+
+    // 赛季中未删除的比赛桌
+    pub async fn get_games_by_season(&self, season_num: i32) -> Result<Vec<LeagueGame>, Error> {
         sqlx::query_as!(
-            LeaguePlayer,
-            "SELECT id, name FROM meetup_league_player WHERE id = $1",
-            id
+            LeagueGame,
+            "SELECT game_time, played_date, registered, season_num, table_num, processed, id, e, s, w, n, session_id, source_gid, description, group_name
+             FROM meetup_league_table WHERE season_num = $1 AND deleted_at IS NULL",
+            season_num
         )
-            .fetch_one(&self.pool)
+            .fetch_all(&self.pool)
             .await
     }
 
-    // 添加通过名称查询玩家的功能
-    pub async fn get_player_by_name(&self, name: &str) -> Result<LeaguePlayer, Error> {
-        sqlx::query_as!(
-            LeaguePlayer,
-            "SELECT id, name FROM meetup_league_player WHERE name = $1",
-            name
+    // 获取所有赛季编号
+    pub async fn get_all_seasons(&self) -> Result<Vec<i32>, Error> {
+        let rows = sqlx::query!("SELECT DISTINCT season_num FROM meetup_league_table WHERE deleted_at IS NULL ORDER BY season_num")
+            .fetch_all(&self.pool).await?;
+        Ok(rows.into_iter().map(|r| r.season_num).collect())
+    }
+
+    // 获取有指定分组比赛的赛季编号
+    pub async fn get_seasons_by_group(&self, group_name: &str) -> Result<Vec<i32>, Error> {
+        let rows = sqlx::query!(
+            "SELECT DISTINCT season_num FROM meetup_league_table
+             WHERE group_name = $1 AND deleted_at IS NULL ORDER BY season_num",
+            group_name
+        ).fetch_all(&self.pool).await?;
+        Ok(rows.into_iter().map(|r| r.season_num).collect())
+    }
+
+    // 获取在指定分组有比赛记录的玩家，可再按赛季过滤
+    pub async fn get_players_by_group(&self, group_name: &str, season_num: Option<i32>) -> Result<Vec<LeaguePlayer>, Error> {
+        let rows = sqlx::query!(
+            r#"SELECT DISTINCT p.id, p.name FROM meetup_league_player p
+                JOIN meetup_league_result r ON p.id = r.player_id
+                JOIN meetup_league_table t ON r.table_id = t.id
+                WHERE t.group_name = $1 AND ($2::INTEGER IS NULL OR t.season_num = $2)
+                  AND r.deleted_at IS NULL AND t.deleted_at IS NULL"#,
+            group_name,
+            season_num
+        ).fetch_all(&self.pool).await?;
+        Ok(rows.into_iter().map(|r| LeaguePlayer { id: r.id, name: r.name }).collect())
+    }
+
+    // 获取指定赛季的所有玩家（有比赛记录的）
+    pub async fn get_players_by_season(&self, season_num: i32) -> Result<Vec<LeaguePlayer>, Error> {
+        let rows = sqlx::query!(
+            r#"SELECT DISTINCT p.id, p.name FROM meetup_league_player p
+                JOIN meetup_league_result r ON p.id = r.player_id
+                JOIN meetup_league_table t ON r.table_id = t.id
+                WHERE t.season_num = $1 AND r.deleted_at IS NULL AND t.deleted_at IS NULL"#,
+            season_num
+        ).fetch_all(&self.pool).await?;
+        Ok(rows.into_iter().map(|r| LeaguePlayer { id: r.id, name: r.name }).collect())
+    }
+
+    // 获取指定赛季指定玩家的所有对战数据
+    pub async fn get_player_matches_by_season(&self, player_id: i32, season_num: i32) -> Result<Vec<GameInfo>, Error> {
+        self.player_matches(player_id, Some(season_num)).await
+    }
+
+    // 玩家参与的比赛桌一次查出，再批量取回成绩，查询次数与比赛数无关
+    async fn player_matches(&self, player_id: i32, season_num: Option<i32>) -> Result<Vec<GameInfo>, Error> {
+        let games = sqlx::query_as!(
+            LeagueGame,
+            r#"SELECT t.game_time, t.played_date, t.registered, t.season_num, t.table_num, t.processed,
+                      t.id, t.e, t.s, t.w, t.n, t.session_id, t.source_gid, t.description, t.group_name
+                FROM meetup_league_result r
+                JOIN meetup_league_table t ON r.table_id = t.id
+                WHERE r.player_id = $1 AND r.deleted_at IS NULL AND t.deleted_at IS NULL
+                  AND ($2::INT IS NULL OR t.season_num = $2)
+                ORDER BY r.id"#,
+            player_id, season_num
+        ).fetch_all(&self.pool).await?;
+        self.build_game_infos(games).await
+    }
+
+    // 批量组装 GameInfo，所有比赛桌的成绩和玩家名在一次查询中取回
+    pub async fn build_game_infos(&self, games: Vec<LeagueGame>) -> Result<Vec<GameInfo>, Error> {
+        let table_ids: Vec<i32> = games.iter().map(|g| g.id).collect();
+        let rows = sqlx::query!(
+            r#"SELECT r.table_id, r.player_id, p.name AS "player_name?", r.result, r.position, r.uma, r.penalty, r.total
+                FROM meetup_league_result r
+                LEFT JOIN meetup_league_player p ON r.player_id = p.id
+                WHERE r.table_id = ANY($1) AND r.deleted_at IS NULL
+                ORDER BY r.table_id, r.id"#,
+            &table_ids
         )
+            .fetch_all(&self.pool)
+            .await?;
+
+        let seats: HashMap<i32, [i32; 4]> = games.iter().map(|g| (g.id, [g.e, g.s, g.w, g.n])).collect();
+        let mut results: HashMap<i32, Vec<PlayerResult>> = HashMap::new();
+        for r in rows {
+            let seat = seats.get(&r.table_id).map(|ids| seat_of(ids, r.player_id)).unwrap_or("?");
+            results.entry(r.table_id).or_default().push(PlayerResult {
+                seat: seat.to_string(),
+                player_name: r.player_name.unwrap_or_else(|| "未知玩家".to_string()),
+                score: r.result,
+                position: r.position,
+                uma: r.uma,
+                penalty: r.penalty,
+                total: r.total,
+            });
+        }
+        Ok(games
+            .into_iter()
+            .map(|game| {
+                let player_results = results.remove(&game.id).unwrap_or_default();
+                game_info(game, player_results)
+            })
+            .collect())
+    }
+
+    // 已记录的最大上游gid，没有记录时为 None
+    pub async fn max_source_gid(&self) -> Result<Option<i32>, Error> {
+        sqlx::query_scalar!("SELECT MAX(source_gid) FROM meetup_league_table")
             .fetch_one(&self.pool)
             .await
     }
+}
 
-    pub async fn update_player(&self, player: &LeaguePlayer) -> Result<PgQueryResult, Error> {
-        sqlx::query!(
-            "UPDATE meetup_league_player SET name = $1 WHERE id = $2",
-            player.name,
-            player.id
-        )
-            .execute(&self.pool)
+// 按东南西北的玩家ID确定座位
+fn seat_of(seat_ids: &[i32; 4], player_id: i32) -> &'static str {
+    match seat_ids.iter().position(|&id| id == player_id) {
+        Some(0) => "E",
+        Some(1) => "S",
+        Some(2) => "W",
+        Some(3) => "N",
+        _ => "?",
+    }
+}
+
+fn game_info(game: LeagueGame, player_results: Vec<PlayerResult>) -> GameInfo {
+    GameInfo {
+        game_id: game.id,
+        // 旧记录没有 played_date 时退回到 game_time
+        played_date: game.played_date.or(game.game_time.map(|dt| dt.date())).unwrap_or_default(),
+        registered: game.registered,
+        // 旧记录没有原始描述时按赛季和桌号生成
+        description: game
+            .description
+            .unwrap_or_else(|| format!("Season {} Table {}", game.season_num, game.table_num)),
+        processed: game.processed,
+        player_results,
+        season_num: game.season_num,
+        table_num: game.table_num,
+        group_name: game.group_name,
+        session_id: game.session_id,
+        source_gid: game.source_gid,
+    }
+}
+
+// 事务作用域的仓库操作，提交前所有写入对其他连接不可见
+// 查询类方法使用 fetch_optional，避免把"未找到"当作错误而中断事务
+pub struct LeagueTransaction {
+    pub(super) tx: Transaction<'static, Postgres>,
+}
+
+impl LeagueTransaction {
+    pub async fn commit(self) -> Result<(), Error> {
+        self.tx.commit().await
+    }
+
+    pub async fn rollback(self) -> Result<(), Error> {
+        self.tx.rollback().await
+    }
+
+    pub async fn list_players(&mut self) -> Result<Vec<LeaguePlayer>, Error> {
+        sqlx::query_as!(LeaguePlayer, "SELECT id, name FROM meetup_league_player")
+            .fetch_all(&mut *self.tx)
             .await
     }
 
-    pub async fn delete_player(&self, id: i32) -> Result<PgQueryResult, Error> {
-        sqlx::query!("DELETE FROM meetup_league_player WHERE id = $1", id)
-            .execute(&self.pool)
+    pub async fn get_player(&mut self, id: i32) -> Result<Option<LeaguePlayer>, Error> {
+        sqlx::query_as!(
+            LeaguePlayer,
+            "SELECT id, name FROM meetup_league_player WHERE id = $1",
+            id
+        )
+            .fetch_optional(&mut *self.tx)
             .await
     }
 
-    pub async fn list_players(&self) -> Result<Vec<LeaguePlayer>, Error> {
-        sqlx::query_as!(LeaguePlayer, "SELECT id, name FROM meetup_league_player")
-            .fetch_all(&self.pool)
+    pub async fn create_player(&mut self, player: &LeaguePlayer) -> Result<i32, Error> {
+        sqlx::query_scalar!(
+        "INSERT INTO meetup_league_player (name) VALUES ($1) RETURNING id",
+        player.name
+    )
+            .fetch_one(&mut *self.tx)
             .await
     }
 
-    // 获取所有玩家
-    pub async fn get_all_players(&self) -> Vec<LeaguePlayer> {
-        match self.list_players().await {
-            Ok(players) => players,
-            Err(_) => vec![],
-        }
+    pub async fn create_player_with_id(&mut self, player: &LeaguePlayer) -> Result<i32, Error> {
+        sqlx::query_scalar!(
+            r#"
+            INSERT INTO meetup_league_player (id, name)
+            OVERRIDING SYSTEM VALUE
+            VALUES ($1, $2)
+            RETURNING id
+            "#,
+            player.id,
+            player.name
+        )
+        .fetch_one(&mut *self.tx)
+        .await
     }
 
-    // 获取指定玩家的所有对战数据（GameInfo）
-    pub async fn get_player_matches(&self, name: &str) -> Vec<crate::models::league::GameInfo> {
-        // 1. 查询玩家ID
-        let player = match self.get_player_by_name(name).await {
-            Ok(p) => p,
-            Err(_) => return vec![],
-        };
-        // 2. 查询该玩家参与的所有table_id
-        let results = match sqlx::query!(
-            "SELECT table_id FROM meetup_league_result WHERE player_id = $1",
+    pub async fn update_player(&mut self, player: &LeaguePlayer) -> Result<PgQueryResult, Error> {
+        // 改名前把旧名字记为曾用名
+        sqlx::query!(
+            r#"
+            WITH old AS (
+                SELECT id, name FROM meetup_league_player WHERE id = $2 AND name <> $1
+            ), alias AS (
+                INSERT INTO meetup_league_player_alias (player_id, name)
+                SELECT id, name FROM old
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM meetup_league_player_alias a WHERE a.player_id = old.id AND a.name = old.name
+                )
+            )
+            UPDATE meetup_league_player SET name = $1 WHERE id = $2
+            "#,
+            player.name,
             player.id
-        ).fetch_all(&self.pool).await {
-            Ok(r) => r,
-            Err(_) => return vec![],
-        };
-        let mut games = Vec::new();
-        for row in results {
-            if let Ok(game) = self.get_game(row.table_id).await {
-                // 查询该table所有玩家结果
-                let db_results = match sqlx::query!(
-                    "SELECT player_id, result, position, uma, penalty, total FROM meetup_league_result WHERE table_id = $1",
-                    row.table_id
-                ).fetch_all(&self.pool).await {
-                    Ok(pr) => pr,
-                    Err(_) => vec![],
-                };
-                // 动态组装 PlayerResult，补 seat 字段
-                let mut player_results = Vec::new();
-                for r in db_results {
-                    let seat = if r.player_id == game.e {
-                        "E"
-                    } else if r.player_id == game.s {
-                        "S"
-                    } else if r.player_id == game.w {
-                        "W"
-                    } else if r.player_id == game.n {
-                        "N"
-                    } else {
-                        "?"
-                    };
-                    // 查询玩家名字
-                    let player_name = match self.get_player(r.player_id).await {
-                        Ok(p) => p.name,
-                        Err(_) => "未知玩家".to_string(),
-                    };
-                    player_results.push(crate::models::league::PlayerResult {
-                        seat: seat.to_string(),
-                        player_name,
-                        score: r.result,
-                        position: r.position,
-                        uma: r.uma,
-                        penalty: r.penalty,
-                        total: r.total,
-                    });
-                }
-                games.push(crate::models::league::GameInfo {
-                    game_id: game.id,
-                    played_date: game.game_time.map(|dt| dt.date()).unwrap_or_default(),
-                    registered: game.game_time,
-                    description: format!("Season {} Table {}", game.season_num, game.table_num),
-                    processed: game.processed,
-                    player_results,
-                    season_num: game.season_num,
-                    table_num: game.table_num,
-                });
-            }
-        }
-        games
+        )
+            .execute(&mut *self.tx)
+            .await
     }
 
-    // LeagueGame CRUD 操作
-pub async fn create_game(&self, game: &LeagueGame) -> Result<i32, Error> {
-            sqlx::query_scalar!(
-            "INSERT INTO meetup_league_table (game_time, season_num, table_num, processed, e, s, w, n)
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+    pub async fn create_game(&mut self, game: &LeagueGame) -> Result<i32, Error> {
+        sqlx::query_scalar!(
+            "INSERT INTO meetup_league_table (game_time, played_date, registered, season_num, table_num, processed, e, s, w, n, session_id, source_gid, description, group_name)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
              RETURNING id",
             game.game_time,
+            game.played_date,
+            game.registered,
             game.season_num,
             game.table_num,
             game.processed,
             game.e,
             game.s,
             game.w,
-            game.n
-        )
-                .fetch_one(&self.pool)
-                .await
-    }
-
-    pub async fn get_game(&self, id: i32) -> Result<LeagueGame, Error> {
-        sqlx::query_as!(
-            LeagueGame,
-            "SELECT game_time, season_num, table_num, processed, id, e, s, w, n
-             FROM meetup_league_table WHERE id = $1",
-            id
+            game.n,
+            game.session_id,
+            game.source_gid,
+            game.description,
+            game.group_name
         )
-            .fetch_one(&self.pool)
+            .fetch_one(&mut *self.tx)
             .await
     }
 
-    pub async fn update_game(&self, game: &LeagueGame) -> Result<PgQueryResult, Error> {
+    pub async fn update_game(&mut self, game: &LeagueGame) -> Result<PgQueryResult, Error> {
         sqlx::query!(
             "UPDATE meetup_league_table SET
-             game_time = $1, season_num = $2, table_num = $3, processed = $4,
-             e = $5, s = $6, w = $7, n = $8
-             WHERE id = $9",
+             game_time = $1, played_date = $2, registered = $3, season_num = $4, table_num = $5, processed = $6,
+             e = $7, s = $8, w = $9, n = $10, session_id = $11, source_gid = $12, description = $13, group_name = $14,
+             deleted_at = NULL, needs_review = FALSE
+             WHERE id = $15",
             game.game_time,
+            game.played_date,
+            game.registered,
             game.season_num,
             game.table_num,
             game.processed,
@@ -205,40 +319,43 @@ pub async fn create_game(&self, game: &LeagueGame) -> Result<i32, Error> {
             game.s,
             game.w,
             game.n,
+            game.session_id,
+            game.source_gid,
+            game.description,
+            game.group_name,
             game.id
         )
-            .execute(&self.pool)
-            .await
-    }
-
-    pub async fn delete_game(&self, id: i32) -> Result<PgQueryResult, Error> {
-        sqlx::query!("DELETE FROM meetup_league_table WHERE id = $1", id)
-            .execute(&self.pool)
+            .execute(&mut *self.tx)
             .await
     }
 
-    pub async fn list_games(&self) -> Result<Vec<LeagueGame>, Error> {
+    pub async fn get_game_by_source_gid(&mut self, source_gid: i32) -> Result<Option<LeagueGame>, Error> {
         sqlx::query_as!(
             LeagueGame,
-            "SELECT game_time, season_num, table_num, processed, id, e, s, w, n FROM meetup_league_table"
+            "SELECT game_time, played_date, registered, season_num, table_num, processed, id, e, s, w, n, session_id, source_gid, description, group_name
+             FROM meetup_league_table WHERE source_gid = $1",
+            source_gid
         )
-            .fetch_all(&self.pool)
+            .fetch_optional(&mut *self.tx)
             .await
     }
 
-    pub async fn get_games_by_season(&self, season_num: i32) -> Result<Vec<LeagueGame>, Error> {
+    // 只匹配尚未记录 source_gid 的旧记录，已关联其他gid的记录不会被覆盖
+    pub async fn get_legacy_game_by_season_and_table(&mut self, season_num: i32, table_num: i32) -> Result<Option<LeagueGame>, Error> {
         sqlx::query_as!(
-            LeagueGame,
-            "SELECT game_time, season_num, table_num, processed, id, e, s, w, n
-             FROM meetup_league_table WHERE season_num = $1",
-            season_num
-        )
-            .fetch_all(&self.pool)
+        LeagueGame,
+        "SELECT game_time, played_date, registered, season_num, table_num, processed, id, e, s, w, n, session_id, source_gid, description, group_name
+         FROM meetup_league_table
+         WHERE season_num = $1 AND table_num = $2 AND source_gid IS NULL
+         ORDER BY id LIMIT 1",
+        season_num,
+        table_num
+    )
+            .fetch_optional(&mut *self.tx)
             .await
     }
 
-    // LeagueResult CRUD 操作
-    pub async fn create_result(&self, result: &LeagueResult) -> Result<PgQueryResult, Error> {
+    pub async fn create_result(&mut self, result: &LeagueResult) -> Result<PgQueryResult, Error> {
         sqlx::query!(
         "INSERT INTO meetup_league_result
          (table_id, player_id, result, position, uma, penalty, total)
@@ -251,26 +368,16 @@ pub async fn create_game(&self, game: &LeagueGame) -> Result<i32, Error> {
         result.penalty,
         result.total
     )
-            .execute(&self.pool)
+            .execute(&mut *self.tx)
             .await
     }
 
-    pub async fn get_result(&self, id: i32) -> Result<LeagueResult, Error> {
-        sqlx::query_as!(
-            LeagueResult,
-            "SELECT id, table_id, player_id, result, position, uma, penalty, total
-             FROM meetup_league_result WHERE id = $1",
-            id
-        )
-            .fetch_one(&self.pool)
-            .await
-    }
-
-    pub async fn update_result(&self, result: &LeagueResult) -> Result<PgQueryResult, Error> {
+    pub async fn update_result(&mut self, result: &LeagueResult) -> Result<PgQueryResult, Error> {
         sqlx::query!(
             "UPDATE meetup_league_result
              SET table_id = $1, player_id = $2, result = $3,
-             position = $4, uma = $5, penalty = $6, total = $7
+             position = $4, uma = $5, penalty = $6, total = $7,
+             deleted_at = NULL, needs_review = FALSE
              WHERE id = $8",
             result.table_id,
             result.player_id,
@@ -281,27 +388,11 @@ pub async fn create_game(&self, game: &LeagueGame) -> Result<i32, Error> {
             result.total,
             result.id
         )
-            .execute(&self.pool)
-            .await
-    }
-
-    pub async fn delete_result(&self, id: i32) -> Result<PgQueryResult, Error> {
-        sqlx::query!("DELETE FROM meetup_league_result WHERE id = $1", id)
-            .execute(&self.pool)
-            .await
-    }
-
-    pub async fn list_results(&self) -> Result<Vec<LeagueResult>, Error> {
-        sqlx::query_as!(
-            LeagueResult,
-            "SELECT id, table_id, player_id, result, position, uma, penalty, total FROM meetup_league_result"
-        )
-            .fetch_all(&self.pool)
+            .execute(&mut *self.tx)
             .await
     }
 
-    // 添加通过桌子ID和玩家ID查询结果的方法
-    pub async fn get_result_by_table_and_player(&self, table_id: i32, player_id: i32) -> Result<LeagueResult, Error> {
+    pub async fn get_result_by_table_and_player(&mut self, table_id: i32, player_id: i32) -> Result<Option<LeagueResult>, Error> {
         sqlx::query_as!(
             LeagueResult,
             "SELECT id, table_id, player_id, result, position, uma, penalty, total
@@ -310,115 +401,48 @@ pub async fn create_game(&self, game: &LeagueGame) -> Result<i32, Error> {
             table_id,
             player_id
         )
-            .fetch_one(&self.pool)
+            .fetch_optional(&mut *self.tx)
             .await
     }
 
-    pub async fn get_game_by_season_and_table(&self, season_num: i32, table_num: i32) -> Result<LeagueGame, Error> {
+    // 以下批量查询供同步前的差异计算使用，与单条查询的匹配规则相同
+    pub async fn get_games_by_source_gids(&mut self, source_gids: &[i32]) -> Result<Vec<LeagueGame>, Error> {
         sqlx::query_as!(
-        LeagueGame,
-        "SELECT game_time, season_num, table_num, processed, id, e, s, w, n
-         FROM meetup_league_table
-         WHERE season_num = $1 AND table_num = $2",
-        season_num,
-        table_num
-    )
-            .fetch_one(&self.pool)
+            LeagueGame,
+            "SELECT game_time, played_date, registered, season_num, table_num, processed, id, e, s, w, n, session_id, source_gid, description, group_name
+             FROM meetup_league_table WHERE source_gid = ANY($1)",
+            source_gids
+        )
+            .fetch_all(&mut *self.tx)
             .await
     }
 
-    // 获取所有赛季编号
-    pub async fn get_all_seasons(&self) -> Vec<i32> {
-        match sqlx::query!("SELECT DISTINCT season_num FROM meetup_league_table ORDER BY season_num")
-            .fetch_all(&self.pool).await {
-            Ok(rows) => rows.into_iter().map(|r| r.season_num).collect(),
-            Err(_) => vec![],
-        }
-    }
-
-    // 获取指定赛季的所有玩家（有比赛记录的）
-    pub async fn get_players_by_season(&self, season_num: i32) -> Vec<crate::models::league::LeaguePlayer> {
-        match sqlx::query!(
-            r#"SELECT DISTINCT p.id, p.name FROM meetup_league_player p
-                JOIN meetup_league_result r ON p.id = r.player_id
-                JOIN meetup_league_table t ON r.table_id = t.id
-                WHERE t.season_num = $1"#,
-            season_num
-        ).fetch_all(&self.pool).await {
-            Ok(rows) => rows.into_iter().map(|r| crate::models::league::LeaguePlayer { id: r.id, name: r.name }).collect(),
-            Err(_) => vec![],
-        }
+    // 按 (赛季, 桌号) 成对查询尚未记录 source_gid 的旧记录，按ID排序，同一桌号取第一条
+    pub async fn get_legacy_games(&mut self, season_nums: &[i32], table_nums: &[i32]) -> Result<Vec<LeagueGame>, Error> {
+        sqlx::query_as!(
+            LeagueGame,
+            "SELECT t.game_time, t.played_date, t.registered, t.season_num, t.table_num, t.processed, t.id, t.e, t.s, t.w, t.n,
+                    t.session_id, t.source_gid, t.description, t.group_name
+             FROM meetup_league_table t
+             JOIN UNNEST($1::INT[], $2::INT[]) AS k(season_num, table_num)
+               ON t.season_num = k.season_num AND t.table_num = k.table_num
+             WHERE t.source_gid IS NULL
+             ORDER BY t.id",
+            season_nums,
+            table_nums
+        )
+            .fetch_all(&mut *self.tx)
+            .await
     }
 
-    // 获取指定赛季指定玩家的所有对战数据
-    pub async fn get_player_matches_by_season(&self, name: &str, season_num: i32) -> Vec<crate::models::league::GameInfo> {
-        // 1. 查询玩家ID
-        let player = match self.get_player_by_name(name).await {
-            Ok(p) => p,
-            Err(_) => return vec![],
-        };
-        // 2. 查询该玩家在该赛季参与的所有table_id
-        let results = match sqlx::query!(
-            r#"SELECT r.table_id FROM meetup_league_result r
-                JOIN meetup_league_table t ON r.table_id = t.id
-                WHERE r.player_id = $1 AND t.season_num = $2"#,
-            player.id, season_num
-        ).fetch_all(&self.pool).await {
-            Ok(r) => r,
-            Err(_) => return vec![],
-        };
-        let mut games = Vec::new();
-        for row in results {
-            if let Ok(game) = self.get_game(row.table_id).await {
-                // 查询该table所有玩家结果
-                let db_results = match sqlx::query!(
-                    "SELECT player_id, result, position, uma, penalty, total FROM meetup_league_result WHERE table_id = $1",
-                    row.table_id
-                ).fetch_all(&self.pool).await {
-                    Ok(pr) => pr,
-                    Err(_) => vec![],
-                };
-                // 动态组装 PlayerResult，补 seat 字段
-                let mut player_results = Vec::new();
-                for r in db_results {
-                    let seat = if r.player_id == game.e {
-                        "E"
-                    } else if r.player_id == game.s {
-                        "S"
-                    } else if r.player_id == game.w {
-                        "W"
-                    } else if r.player_id == game.n {
-                        "N"
-                    } else {
-                        "?"
-                    };
-                    // 查询玩家名字
-                    let player_name = match self.get_player(r.player_id).await {
-                        Ok(p) => p.name,
-                        Err(_) => "Unknown Player".to_string(),
-                    };
-                    player_results.push(crate::models::league::PlayerResult {
-                        seat: seat.to_string(),
-                        player_name,
-                        score: r.result,
-                        position: r.position,
-                        uma: r.uma,
-                        penalty: r.penalty,
-                        total: r.total,
-                    });
-                }
-                games.push(crate::models::league::GameInfo {
-                    game_id: game.id,
-                    played_date: game.game_time.map(|dt| dt.date()).unwrap_or_default(),
-                    registered: game.game_time,
-                    description: format!("Season {} Table {}", game.season_num, game.table_num),
-                    processed: game.processed,
-                    player_results,
-                    season_num: game.season_num,
-                    table_num: game.table_num,
-                });
-            }
-        }
-        games
+    pub async fn get_results_by_tables(&mut self, table_ids: &[i32]) -> Result<Vec<LeagueResult>, Error> {
+        sqlx::query_as!(
+            LeagueResult,
+            "SELECT id, table_id, player_id, result, position, uma, penalty, total
+             FROM meetup_league_result WHERE table_id = ANY($1) ORDER BY id",
+            table_ids
+        )
+            .fetch_all(&mut *self.tx)
+            .await
     }
-}
\ No newline at end of file
+}
@@ -1,2 +1,11 @@
 pub mod ankan;
-pub use ankan::LeagueRepository;
+mod player_alias;
+mod player_summary;
+mod quarantine;
+mod reconcile;
+mod session;
+mod snapshot;
+mod standings;
+mod sync_run;
+mod webhook;
+pub use ankan::{LeagueRepository, LeagueTransaction};
@@ -0,0 +1,99 @@
+use sqlx::Error;
+use crate::db::{LeagueRepository, LeagueTransaction};
+use crate::models::league::{LeaguePlayer, PlayerMerge};
+use std::collections::HashMap;
+
+impl LeagueRepository {
+    // 所有曾用名及其玩家ID，最近记录的在前
+    pub async fn list_player_aliases(&self) -> Result<Vec<(String, i32)>, Error> {
+        let rows = sqlx::query!(
+            "SELECT name, player_id FROM meetup_league_player_alias ORDER BY created_at DESC, id DESC"
+        )
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.into_iter().map(|r| (r.name, r.player_id)).collect())
+    }
+}
+
+impl LeagueTransaction {
+    // 已合并的玩家ID到合并目标ID的映射
+    pub async fn merged_player_ids(&mut self) -> Result<HashMap<i32, i32>, Error> {
+        let rows = sqlx::query!(
+            r#"SELECT merged_from AS "merged_from!", player_id FROM meetup_league_player_alias
+                WHERE merged_from IS NOT NULL"#
+        )
+            .fetch_all(&mut *self.tx)
+            .await?;
+        Ok(rows.into_iter().map(|r| (r.merged_from, r.player_id)).collect())
+    }
+
+    // 两名玩家同桌的比赛数，大于0时不能合并
+    pub async fn count_shared_tables(&mut self, a: i32, b: i32) -> Result<i64, Error> {
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM meetup_league_table
+                WHERE $1 IN (e, s, w, n) AND $2 IN (e, s, w, n)"#,
+            a,
+            b
+        )
+            .fetch_one(&mut *self.tx)
+            .await
+    }
+
+    // 把 from 的成绩、座位和曾用名转到 into 名下，然后删除 from
+    pub async fn merge_players(&mut self, from: LeaguePlayer, into: LeaguePlayer) -> Result<PlayerMerge, Error> {
+        let results_moved = sqlx::query!(
+            "UPDATE meetup_league_result SET player_id = $2 WHERE player_id = $1",
+            from.id,
+            into.id
+        )
+            .execute(&mut *self.tx)
+            .await?
+            .rows_affected();
+
+        let tables_updated = sqlx::query!(
+            r#"
+            UPDATE meetup_league_table SET
+                e = CASE WHEN e = $1 THEN $2 ELSE e END,
+                s = CASE WHEN s = $1 THEN $2 ELSE s END,
+                w = CASE WHEN w = $1 THEN $2 ELSE w END,
+                n = CASE WHEN n = $1 THEN $2 ELSE n END
+            WHERE $1 IN (e, s, w, n)
+            "#,
+            from.id,
+            into.id
+        )
+            .execute(&mut *self.tx)
+            .await?
+            .rows_affected();
+
+        let aliases_moved = sqlx::query!(
+            "UPDATE meetup_league_player_alias SET player_id = $2 WHERE player_id = $1",
+            from.id,
+            into.id
+        )
+            .execute(&mut *self.tx)
+            .await?
+            .rows_affected();
+
+        sqlx::query!(
+            "INSERT INTO meetup_league_player_alias (player_id, name, merged_from) VALUES ($1, $2, $3)",
+            into.id,
+            from.name,
+            from.id
+        )
+            .execute(&mut *self.tx)
+            .await?;
+
+        sqlx::query!("DELETE FROM meetup_league_player WHERE id = $1", from.id)
+            .execute(&mut *self.tx)
+            .await?;
+
+        Ok(PlayerMerge {
+            from,
+            into,
+            results_moved,
+            tables_updated,
+            aliases_moved,
+        })
+    }
+}
@@ -0,0 +1,100 @@
+use sqlx::Error;
+use crate::db::LeagueRepository;
+use crate::models::league::{LeaguePlayer, PlacementCounts, PlayerSummary, SummaryGame, TablemateSummary};
+
+impl LeagueRepository {
+    // 汇总玩家的成绩，season_num 为空时统计所有赛季
+    pub async fn get_player_summary(&self, player: LeaguePlayer, season_num: Option<i32>) -> Result<PlayerSummary, Error> {
+        let totals = sqlx::query!(
+            r#"SELECT COUNT(*) AS "games!",
+                      COALESCE(SUM(r.result), 0) AS "score_sum!",
+                      COALESCE(SUM(r.uma), 0) AS "uma_sum!",
+                      COALESCE(SUM(r.penalty), 0) AS "penalty_sum!",
+                      COALESCE(SUM(r.total), 0) AS "total_sum!",
+                      AVG(r.result) AS average_score,
+                      AVG(r.total) AS average_total,
+                      AVG(r.position::FLOAT8) AS average_position,
+                      COUNT(*) FILTER (WHERE r.position = 1) AS "first!",
+                      COUNT(*) FILTER (WHERE r.position = 2) AS "second!",
+                      COUNT(*) FILTER (WHERE r.position = 3) AS "third!",
+                      COUNT(*) FILTER (WHERE r.position = 4) AS "fourth!"
+                FROM meetup_league_result r
+                JOIN meetup_league_table t ON r.table_id = t.id
+                WHERE r.player_id = $1 AND ($2::INTEGER IS NULL OR t.season_num = $2)
+                  AND r.deleted_at IS NULL AND t.deleted_at IS NULL"#,
+            player.id,
+            season_num
+        )
+            .fetch_one(&self.pool)
+            .await?;
+
+        // 总分最高和最低的一场，同分时取较早的比赛
+        let games = sqlx::query_as!(
+            SummaryGame,
+            r#"(SELECT t.id AS "game_id!", t.season_num AS "season_num!", t.table_num AS "table_num!",
+                       t.description, t.played_date, r.result AS "score!", r.position AS "position!", r.total AS "total!"
+                FROM meetup_league_result r
+                JOIN meetup_league_table t ON r.table_id = t.id
+                WHERE r.player_id = $1 AND ($2::INTEGER IS NULL OR t.season_num = $2)
+                  AND r.deleted_at IS NULL AND t.deleted_at IS NULL
+                ORDER BY r.total DESC, t.game_time, t.id LIMIT 1)
+               UNION ALL
+               (SELECT t.id, t.season_num, t.table_num, t.description, t.played_date, r.result, r.position, r.total
+                FROM meetup_league_result r
+                JOIN meetup_league_table t ON r.table_id = t.id
+                WHERE r.player_id = $1 AND ($2::INTEGER IS NULL OR t.season_num = $2)
+                  AND r.deleted_at IS NULL AND t.deleted_at IS NULL
+                ORDER BY r.total ASC, t.game_time, t.id LIMIT 1)"#,
+            player.id,
+            season_num
+        )
+            .fetch_all(&self.pool)
+            .await?;
+        let mut games = games.into_iter();
+
+        // 同桌玩家按本人在同桌时的总分合计排序，第一个为最佳，最后一个为最差
+        let tablemates = sqlx::query_as!(
+            TablemateSummary,
+            r#"SELECT p.name AS player_name, COUNT(*) AS "games!", SUM(mine.total) AS "total!"
+                FROM meetup_league_result mine
+                JOIN meetup_league_table t ON mine.table_id = t.id
+                JOIN meetup_league_result other ON other.table_id = mine.table_id
+                     AND other.player_id <> mine.player_id AND other.deleted_at IS NULL
+                JOIN meetup_league_player p ON other.player_id = p.id
+                WHERE mine.player_id = $1 AND ($2::INTEGER IS NULL OR t.season_num = $2)
+                  AND mine.deleted_at IS NULL AND t.deleted_at IS NULL
+                GROUP BY p.id, p.name
+                ORDER BY SUM(mine.total) DESC, p.name"#,
+            player.id,
+            season_num
+        )
+            .fetch_all(&self.pool)
+            .await?;
+        let rate = |count: i64| (totals.games > 0).then(|| count as f64 / totals.games as f64);
+        Ok(PlayerSummary {
+            player_id: player.id,
+            player_name: player.name,
+            season_num,
+            games: totals.games,
+            score_sum: totals.score_sum,
+            uma_sum: totals.uma_sum,
+            penalty_sum: totals.penalty_sum,
+            total_sum: totals.total_sum,
+            average_score: totals.average_score,
+            average_total: totals.average_total,
+            average_position: totals.average_position,
+            top_rate: rate(totals.first),
+            last_avoidance_rate: rate(totals.games - totals.fourth),
+            placements: PlacementCounts {
+                first: totals.first,
+                second: totals.second,
+                third: totals.third,
+                fourth: totals.fourth,
+            },
+            best_game: games.next(),
+            worst_game: games.next(),
+            best_tablemate: tablemates.first().cloned(),
+            worst_tablemate: tablemates.last().cloned(),
+        })
+    }
+}
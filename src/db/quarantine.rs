@@ -0,0 +1,73 @@
+use sqlx::{Error, postgres::PgQueryResult};
+use crate::db::{LeagueRepository, LeagueTransaction};
+use crate::models::sync::QuarantinedGame;
+use std::collections::HashSet;
+
+impl LeagueRepository {
+    // 列出隔离中的比赛，include_released 为 true 时包括已放行的
+    pub async fn list_quarantine(&self, include_released: bool) -> Result<Vec<QuarantinedGame>, Error> {
+        sqlx::query_as!(
+            QuarantinedGame,
+            "SELECT id, source_gid, description, reasons, payload, first_seen, last_seen, released_at
+             FROM sync_quarantine
+             WHERE $1 OR released_at IS NULL
+             ORDER BY source_gid",
+            include_released
+        )
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    // 放行隔离中的比赛，之后的同步不再校验该gid
+    pub async fn release_quarantine(&self, source_gid: i32) -> Result<Option<QuarantinedGame>, Error> {
+        sqlx::query_as!(
+            QuarantinedGame,
+            "UPDATE sync_quarantine SET released_at = COALESCE(released_at, NOW())
+             WHERE source_gid = $1
+             RETURNING id, source_gid, description, reasons, payload, first_seen, last_seen, released_at",
+            source_gid
+        )
+            .fetch_optional(&self.pool)
+            .await
+    }
+}
+
+impl LeagueTransaction {
+    pub async fn released_quarantine_gids(&mut self) -> Result<HashSet<i32>, Error> {
+        let rows = sqlx::query_scalar!("SELECT source_gid FROM sync_quarantine WHERE released_at IS NOT NULL")
+            .fetch_all(&mut *self.tx)
+            .await?;
+        Ok(rows.into_iter().collect())
+    }
+
+    // 隔离未通过校验的比赛，已隔离时更新原因和数据
+    pub async fn quarantine_game(
+        &mut self,
+        source_gid: i32,
+        description: &str,
+        reasons: &[String],
+        payload: serde_json::Value,
+    ) -> Result<PgQueryResult, Error> {
+        sqlx::query!(
+            "INSERT INTO sync_quarantine (source_gid, description, reasons, payload) VALUES ($1, $2, $3, $4)
+             ON CONFLICT (source_gid) DO UPDATE SET
+             description = $2, reasons = $3, payload = $4, last_seen = NOW()",
+            source_gid,
+            description,
+            reasons,
+            payload
+        )
+            .execute(&mut *self.tx)
+            .await
+    }
+
+    // 上游修正后重新通过校验，一次移除这些比赛未放行的隔离记录
+    pub async fn clear_quarantine(&mut self, source_gids: &[i32]) -> Result<PgQueryResult, Error> {
+        sqlx::query!(
+            "DELETE FROM sync_quarantine WHERE source_gid = ANY($1) AND released_at IS NULL",
+            source_gids
+        )
+            .execute(&mut *self.tx)
+            .await
+    }
+}
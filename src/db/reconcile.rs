@@ -0,0 +1,81 @@
+use sqlx::{Error, postgres::PgQueryResult};
+use crate::db::LeagueTransaction;
+use crate::models::sync::{OrphanResult, OrphanTable};
+
+// 对账用到的查询和批量处理，只涉及记录了 source_gid 的比赛桌
+impl LeagueTransaction {
+    // 所有未删除、已关联上游gid的比赛桌
+    pub async fn list_linked_tables(&mut self) -> Result<Vec<OrphanTable>, Error> {
+        sqlx::query_as!(
+            OrphanTable,
+            r#"SELECT id, source_gid AS "source_gid!", description
+               FROM meetup_league_table
+               WHERE source_gid IS NOT NULL AND deleted_at IS NULL
+               ORDER BY source_gid"#
+        )
+            .fetch_all(&mut *self.tx)
+            .await
+    }
+
+    // 上述比赛桌中所有未删除的成绩
+    pub async fn list_linked_results(&mut self) -> Result<Vec<OrphanResult>, Error> {
+        sqlx::query_as!(
+            OrphanResult,
+            r#"SELECT r.id, r.table_id, t.source_gid AS "source_gid!", r.player_id, p.name AS player_name
+               FROM meetup_league_result r
+               JOIN meetup_league_table t ON r.table_id = t.id
+               JOIN meetup_league_player p ON r.player_id = p.id
+               WHERE t.source_gid IS NOT NULL AND t.deleted_at IS NULL AND r.deleted_at IS NULL
+               ORDER BY t.source_gid, r.id"#
+        )
+            .fetch_all(&mut *self.tx)
+            .await
+    }
+
+    // 删除比赛桌及其成绩
+    pub async fn delete_tables(&mut self, ids: &[i32]) -> Result<PgQueryResult, Error> {
+        sqlx::query!("DELETE FROM meetup_league_result WHERE table_id = ANY($1)", ids)
+            .execute(&mut *self.tx)
+            .await?;
+        sqlx::query!("DELETE FROM meetup_league_table WHERE id = ANY($1)", ids)
+            .execute(&mut *self.tx)
+            .await
+    }
+
+    // 软删除比赛桌及其成绩
+    pub async fn soft_delete_tables(&mut self, ids: &[i32]) -> Result<PgQueryResult, Error> {
+        sqlx::query!(
+            "UPDATE meetup_league_result SET deleted_at = NOW() WHERE table_id = ANY($1) AND deleted_at IS NULL",
+            ids
+        )
+            .execute(&mut *self.tx)
+            .await?;
+        sqlx::query!("UPDATE meetup_league_table SET deleted_at = NOW() WHERE id = ANY($1)", ids)
+            .execute(&mut *self.tx)
+            .await
+    }
+
+    pub async fn flag_tables(&mut self, ids: &[i32]) -> Result<PgQueryResult, Error> {
+        sqlx::query!("UPDATE meetup_league_table SET needs_review = TRUE WHERE id = ANY($1)", ids)
+            .execute(&mut *self.tx)
+            .await
+    }
+
+    pub async fn delete_results(&mut self, ids: &[i32]) -> Result<PgQueryResult, Error> {
+        sqlx::query!("DELETE FROM meetup_league_result WHERE id = ANY($1)", ids)
+            .execute(&mut *self.tx)
+            .await
+    }
+
+    pub async fn soft_delete_results(&mut self, ids: &[i32]) -> Result<PgQueryResult, Error> {
+        sqlx::query!("UPDATE meetup_league_result SET deleted_at = NOW() WHERE id = ANY($1)", ids)
+            .execute(&mut *self.tx)
+            .await
+    }
+
+    pub async fn flag_results(&mut self, ids: &[i32]) -> Result<PgQueryResult, Error> {
+        sqlx::query!("UPDATE meetup_league_result SET needs_review = TRUE WHERE id = ANY($1)", ids)
+            .execute(&mut *self.tx)
+            .await
+    }
+}
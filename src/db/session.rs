@@ -0,0 +1,73 @@
+use sqlx::{Error, postgres::PgQueryResult};
+use crate::db::{LeagueRepository, LeagueTransaction};
+use crate::models::league::{GameInfo, LeagueGame, LeagueSession};
+
+impl LeagueRepository {
+    // 按日期列出所有场次
+    pub async fn list_sessions(&self) -> Result<Vec<LeagueSession>, Error> {
+        sqlx::query_as!(
+            LeagueSession,
+            "SELECT id, name, group_name, date FROM meetup_league_session ORDER BY date, id"
+        )
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    pub async fn get_session(&self, id: i32) -> Result<Option<LeagueSession>, Error> {
+        sqlx::query_as!(
+            LeagueSession,
+            "SELECT id, name, group_name, date FROM meetup_league_session WHERE id = $1",
+            id
+        )
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    // 获取指定场次的所有比赛，按桌号排序
+    pub async fn get_session_games(&self, session_id: i32) -> Result<Vec<GameInfo>, Error> {
+        let games = sqlx::query_as!(
+            LeagueGame,
+            "SELECT game_time, played_date, registered, season_num, table_num, processed, id, e, s, w, n, session_id, source_gid, description, group_name
+             FROM meetup_league_table WHERE session_id = $1 AND deleted_at IS NULL ORDER BY table_num",
+            session_id
+        )
+            .fetch_all(&self.pool)
+            .await?;
+        self.build_game_infos(games).await
+    }
+
+    // 获取指定场次中指定玩家的对战数据，可同时按赛季过滤
+    pub async fn get_player_matches_by_session(&self, player_id: i32, session_id: i32, season_num: Option<i32>) -> Result<Vec<GameInfo>, Error> {
+        let games = sqlx::query_as!(
+            LeagueGame,
+            r#"SELECT t.game_time, t.played_date, t.registered, t.season_num, t.table_num, t.processed,
+                      t.id, t.e, t.s, t.w, t.n, t.session_id, t.source_gid, t.description, t.group_name
+                FROM meetup_league_table t
+                JOIN meetup_league_result r ON r.table_id = t.id
+                WHERE r.player_id = $1 AND t.session_id = $2
+                  AND r.deleted_at IS NULL AND t.deleted_at IS NULL
+                  AND ($3::INT IS NULL OR t.season_num = $3)
+                ORDER BY t.table_num"#,
+            player_id, session_id, season_num
+        )
+            .fetch_all(&self.pool)
+            .await?;
+        self.build_game_infos(games).await
+    }
+}
+
+impl LeagueTransaction {
+    // 按上游 sid 写入场次，已存在时更新
+    pub async fn upsert_session(&mut self, session: &LeagueSession) -> Result<PgQueryResult, Error> {
+        sqlx::query!(
+            "INSERT INTO meetup_league_session (id, name, group_name, date) VALUES ($1, $2, $3, $4)
+             ON CONFLICT (id) DO UPDATE SET name = $2, group_name = $3, date = $4",
+            session.id,
+            session.name,
+            session.group_name,
+            session.date
+        )
+            .execute(&mut *self.tx)
+            .await
+    }
+}
@@ -0,0 +1,106 @@
+use sqlx::{Error, postgres::PgQueryResult};
+use crate::db::LeagueRepository;
+use crate::models::sync::SyncSnapshot;
+
+impl LeagueRepository {
+    // 指定数据源最近一次成功导入的快照，用于条件请求和内容比较
+    pub async fn latest_imported_snapshot(&self, source: &str) -> Result<Option<SyncSnapshot>, Error> {
+        sqlx::query_as!(
+            SyncSnapshot,
+            r#"SELECT id, source, content_hash, etag, last_modified, size_bytes,
+                      fetched_at, last_seen, imported_at
+                FROM sync_snapshot WHERE source = $1 AND imported_at IS NOT NULL
+                ORDER BY imported_at DESC LIMIT 1"#,
+            source
+        )
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    // 存档压缩后的数据，内容已存在时只更新响应头和最近出现时间
+    pub async fn save_snapshot(
+        &self,
+        source: &str,
+        content_hash: &str,
+        etag: Option<&str>,
+        last_modified: Option<&str>,
+        size_bytes: i32,
+        data: &[u8],
+    ) -> Result<i32, Error> {
+        sqlx::query_scalar!(
+            "INSERT INTO sync_snapshot (source, content_hash, etag, last_modified, size_bytes, data)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (source, content_hash) DO UPDATE SET
+             etag = EXCLUDED.etag, last_modified = EXCLUDED.last_modified, last_seen = NOW()
+             RETURNING id",
+            source,
+            content_hash,
+            etag,
+            last_modified,
+            size_bytes,
+            data
+        )
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    // 数据源返回304或内容未变化时更新最近出现时间
+    pub async fn touch_snapshot(&self, id: i32) -> Result<PgQueryResult, Error> {
+        sqlx::query!("UPDATE sync_snapshot SET last_seen = NOW() WHERE id = $1", id)
+            .execute(&self.pool)
+            .await
+    }
+
+    pub async fn mark_snapshot_imported(&self, id: i32) -> Result<PgQueryResult, Error> {
+        sqlx::query!("UPDATE sync_snapshot SET imported_at = NOW() WHERE id = $1", id)
+            .execute(&self.pool)
+            .await
+    }
+
+    // 快照导入之后是否有比赛被放行，有则内容未变化也需要重新导入
+    pub async fn quarantine_released_since_import(&self, snapshot_id: i32) -> Result<bool, Error> {
+        sqlx::query_scalar!(
+            r#"SELECT EXISTS (
+                SELECT 1 FROM sync_quarantine q, sync_snapshot s
+                WHERE s.id = $1 AND q.released_at > s.imported_at
+            ) AS "released!""#,
+            snapshot_id
+        )
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    // 按拉取时间倒序列出快照，可按数据源过滤
+    pub async fn list_snapshots(&self, source: Option<&str>, limit: i64) -> Result<Vec<SyncSnapshot>, Error> {
+        sqlx::query_as!(
+            SyncSnapshot,
+            r#"SELECT id, source, content_hash, etag, last_modified, size_bytes,
+                      fetched_at, last_seen, imported_at
+                FROM sync_snapshot WHERE $1::VARCHAR IS NULL OR source = $1
+                ORDER BY fetched_at DESC, id DESC LIMIT $2"#,
+            source,
+            limit
+        )
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    pub async fn get_snapshot(&self, id: i32) -> Result<Option<SyncSnapshot>, Error> {
+        sqlx::query_as!(
+            SyncSnapshot,
+            r#"SELECT id, source, content_hash, etag, last_modified, size_bytes,
+                      fetched_at, last_seen, imported_at
+                FROM sync_snapshot WHERE id = $1"#,
+            id
+        )
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    // 压缩后的原始数据
+    pub async fn get_snapshot_data(&self, id: i32) -> Result<Option<Vec<u8>>, Error> {
+        sqlx::query_scalar!("SELECT data FROM sync_snapshot WHERE id = $1", id)
+            .fetch_optional(&self.pool)
+            .await
+    }
+}
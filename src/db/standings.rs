@@ -0,0 +1,51 @@
+use sqlx::Error;
+use crate::db::LeagueRepository;
+use crate::models::league::StandingRow;
+
+impl LeagueRepository {
+    // 按玩家汇总赛季成绩，可按分组过滤；名次和资格由调用方按排名规则计算
+    pub async fn get_season_standings(&self, season_num: i32, group_name: Option<&str>) -> Result<Vec<StandingRow>, Error> {
+        let rows = sqlx::query!(
+            r#"SELECT p.id AS player_id, p.name AS player_name,
+                      COUNT(*) AS "games!",
+                      SUM(r.total) AS "total_points!",
+                      AVG(r.total) AS "average_points!",
+                      AVG(r.position::FLOAT8) AS "average_position!",
+                      COUNT(*) FILTER (WHERE r.position = 1) AS "first_count!",
+                      COUNT(*) FILTER (WHERE r.position = 2) AS "second_count!",
+                      COUNT(*) FILTER (WHERE r.position = 3) AS "third_count!",
+                      COUNT(*) FILTER (WHERE r.position = 4) AS "fourth_count!",
+                      SUM(r.uma) AS "uma_sum!",
+                      SUM(r.penalty) AS "penalty_sum!"
+                FROM meetup_league_result r
+                JOIN meetup_league_table t ON r.table_id = t.id
+                JOIN meetup_league_player p ON r.player_id = p.id
+                WHERE t.season_num = $1 AND ($2::VARCHAR IS NULL OR t.group_name = $2)
+                  AND r.deleted_at IS NULL AND t.deleted_at IS NULL
+                GROUP BY p.id, p.name"#,
+            season_num,
+            group_name
+        )
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|r| StandingRow {
+                rank: None,
+                eligible: true,
+                player_id: r.player_id,
+                player_name: r.player_name,
+                games: r.games,
+                total_points: r.total_points,
+                average_points: r.average_points,
+                average_position: r.average_position,
+                first_count: r.first_count,
+                second_count: r.second_count,
+                third_count: r.third_count,
+                fourth_count: r.fourth_count,
+                uma_sum: r.uma_sum,
+                penalty_sum: r.penalty_sum,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,79 @@
+use sqlx::{Error, postgres::PgQueryResult};
+use crate::db::LeagueRepository;
+use crate::models::sync::SyncRun;
+use crate::sync::{SyncError, SyncSummary};
+
+impl LeagueRepository {
+    // 同步开始时创建运行记录，状态为 running
+    pub async fn create_sync_run(&self, source: &str, triggered_by: &str) -> Result<i32, Error> {
+        sqlx::query_scalar!(
+            "INSERT INTO sync_run (source, triggered_by, status) VALUES ($1, $2, 'running') RETURNING id",
+            source,
+            triggered_by
+        )
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    // 同步结束时写入最终状态和统计
+    pub async fn finish_sync_run(
+        &self,
+        id: i32,
+        status: &str,
+        summary: &SyncSummary,
+        error: Option<&SyncError>,
+    ) -> Result<PgQueryResult, Error> {
+        sqlx::query!(
+            "UPDATE sync_run SET
+             status = $1, finished_at = NOW(),
+             players_created = $2, players_renamed = $3,
+             games_saved = $4, games_updated = $5, results_written = $6,
+             orphan_tables = $7, orphan_results = $8, games_quarantined = $9,
+             warnings = $10, error = $11, error_kind = $12, snapshot_id = $13
+             WHERE id = $14",
+            status,
+            summary.created_players_count as i32,
+            summary.updated_players_count as i32,
+            summary.saved_count as i32,
+            summary.updated_games_count as i32,
+            summary.results_written_count as i32,
+            summary.orphan_tables_count as i32,
+            summary.orphan_results_count as i32,
+            summary.quarantined_count as i32,
+            &summary.warnings,
+            error.map(|e| e.to_string()),
+            error.map(|e| e.kind()),
+            summary.snapshot_id,
+            id
+        )
+            .execute(&self.pool)
+            .await
+    }
+
+    pub async fn get_sync_run(&self, id: i32) -> Result<Option<SyncRun>, Error> {
+        sqlx::query_as!(
+            SyncRun,
+            "SELECT id, source, triggered_by, status, started_at, finished_at,
+             players_created, players_renamed, games_saved, games_updated, results_written,
+             orphan_tables, orphan_results, games_quarantined, snapshot_id, warnings, error, error_kind
+             FROM sync_run WHERE id = $1",
+            id
+        )
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    // 按开始时间倒序列出最近的运行记录
+    pub async fn list_sync_runs(&self, limit: i64) -> Result<Vec<SyncRun>, Error> {
+        sqlx::query_as!(
+            SyncRun,
+            "SELECT id, source, triggered_by, status, started_at, finished_at,
+             players_created, players_renamed, games_saved, games_updated, results_written,
+             orphan_tables, orphan_results, games_quarantined, snapshot_id, warnings, error, error_kind
+             FROM sync_run ORDER BY started_at DESC, id DESC LIMIT $1",
+            limit
+        )
+            .fetch_all(&self.pool)
+            .await
+    }
+}
@@ -0,0 +1,64 @@
+use sqlx::{Error, postgres::PgQueryResult};
+use crate::db::LeagueRepository;
+use crate::models::sync::WebhookDelivery;
+
+impl LeagueRepository {
+    // 投递前登记，状态为 pending
+    pub async fn create_webhook_delivery(
+        &self,
+        webhook: &str,
+        url: &str,
+        event: &str,
+        run_id: i32,
+        payload: &serde_json::Value,
+    ) -> Result<i32, Error> {
+        sqlx::query_scalar!(
+            "INSERT INTO webhook_delivery (webhook, url, event, run_id, payload)
+             VALUES ($1, $2, $3, $4, $5) RETURNING id",
+            webhook,
+            url,
+            event,
+            run_id,
+            payload
+        )
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    // 记录一次投递尝试的结果
+    pub async fn record_webhook_attempt(
+        &self,
+        id: i32,
+        status: &str,
+        attempts: i32,
+        response_status: Option<i32>,
+        error: Option<&str>,
+    ) -> Result<PgQueryResult, Error> {
+        sqlx::query!(
+            "UPDATE webhook_delivery SET
+             status = $2::VARCHAR, attempts = $3, response_status = $4, last_error = $5,
+             delivered_at = CASE WHEN $2::VARCHAR = 'delivered' THEN NOW() END
+             WHERE id = $1",
+            id,
+            status,
+            attempts,
+            response_status,
+            error
+        )
+            .execute(&self.pool)
+            .await
+    }
+
+    // 按创建时间倒序列出投递记录
+    pub async fn list_webhook_deliveries(&self, limit: i64) -> Result<Vec<WebhookDelivery>, Error> {
+        sqlx::query_as!(
+            WebhookDelivery,
+            "SELECT id, webhook, url, event, run_id, payload, status, attempts,
+             response_status, last_error, created_at, delivered_at
+             FROM webhook_delivery ORDER BY created_at DESC, id DESC LIMIT $1",
+            limit
+        )
+            .fetch_all(&self.pool)
+            .await
+    }
+}
@@ -0,0 +1,137 @@
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+use crate::sync::SyncError;
+use crate::sync::fetch::{FetchError, FetchErrorKind};
+
+// 接口错误，统一返回 {"error": 错误类型, "message": 说明} 的 JSON
+#[derive(Debug)]
+pub enum AppError {
+    // 玩家、赛季或场次不存在
+    NotFound(String),
+    // 查询参数无法解析或取值不合法
+    BadRequest(String),
+    // 与同步等正在进行的操作冲突
+    Conflict(String),
+    // 已有同步在运行，响应中附带正在运行的任务ID
+    SyncRunning { job_id: Option<i32>, message: String },
+    // 数据源拉取或解析失败
+    Upstream(String),
+    // 快照解压等服务端内部错误
+    Internal(String),
+    // 数据库不可用或查询失败
+    Database(sqlx::Error),
+}
+
+impl AppError {
+    // 连接池超时、已关闭或网络中断视为数据库不可用，其余数据库错误按服务端错误处理
+    fn database_unavailable(e: &sqlx::Error) -> bool {
+        matches!(e, sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed | sqlx::Error::Io(_))
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            AppError::Conflict(_) | AppError::SyncRunning { .. } => StatusCode::CONFLICT,
+            AppError::Upstream(_) => StatusCode::BAD_GATEWAY,
+            AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::Database(e) if Self::database_unavailable(e) => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            AppError::NotFound(_) => "not_found",
+            AppError::BadRequest(_) => "bad_request",
+            AppError::Conflict(_) => "conflict",
+            AppError::SyncRunning { .. } => "already_running",
+            AppError::Upstream(_) => "upstream_error",
+            AppError::Internal(_) => "internal_error",
+            AppError::Database(e) if Self::database_unavailable(e) => "database_unavailable",
+            AppError::Database(_) => "database_error",
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::NotFound(message)
+            | AppError::BadRequest(message)
+            | AppError::Conflict(message)
+            | AppError::SyncRunning { message, .. }
+            | AppError::Upstream(message)
+            | AppError::Internal(message) => write!(f, "{}", message),
+            AppError::Database(e) => write!(f, "数据库查询失败: {}", e),
+        }
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(e: sqlx::Error) -> Self {
+        AppError::Database(e)
+    }
+}
+
+impl From<FetchError> for AppError {
+    fn from(e: FetchError) -> Self {
+        // 数据源本身的问题返回502，本地问题返回500
+        match e.kind {
+            FetchErrorKind::Database | FetchErrorKind::Config => AppError::Internal(e.to_string()),
+            _ => AppError::Upstream(e.to_string()),
+        }
+    }
+}
+
+impl From<SyncError> for AppError {
+    fn from(e: SyncError) -> Self {
+        match e {
+            SyncError::AlreadyRunning { job_id, .. } => AppError::SyncRunning { job_id, message: e.to_string() },
+            SyncError::Fetch(e) => e.into(),
+            SyncError::Database(e) => AppError::Database(e),
+            e => AppError::Internal(e.to_string()),
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        match &self {
+            AppError::Database(e) => println!("❌ 接口查询数据库失败: {}", e),
+            AppError::Internal(message) => println!("❌ 接口处理失败: {}", message),
+            _ => {}
+        }
+        let mut body = json!({ "error": self.kind(), "message": self.to_string() });
+        if let AppError::SyncRunning { job_id, .. } = &self {
+            body["job_id"] = json!(job_id);
+        }
+        (self.status(), Json(body)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pool_and_connection_errors_are_unavailable() {
+        let io = sqlx::Error::Io(std::io::ErrorKind::ConnectionRefused.into());
+        for e in [sqlx::Error::PoolTimedOut, sqlx::Error::PoolClosed, io] {
+            let err = AppError::from(e);
+            assert_eq!(err.status(), StatusCode::SERVICE_UNAVAILABLE);
+            assert_eq!(err.kind(), "database_unavailable");
+        }
+    }
+
+    #[test]
+    fn other_database_errors_are_internal() {
+        let err = AppError::from(sqlx::Error::RowNotFound);
+        assert_eq!(err.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(err.kind(), "database_error");
+    }
+}
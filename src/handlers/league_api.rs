@@ -1,60 +1,378 @@
 use axum::{extract::{State, Path, Query}, Json};
 use crate::db::LeagueRepository;
-use crate::models::league::{GameInfo};
+use crate::error::AppError;
+use crate::models::league::{GameInfo, LeaguePlayer, LeagueSession, PlayerMerge, PlayerSummary, SeasonMatches, SeasonStandings, SessionInfo, StandingRow};
+use crate::sync::SYNC_STATE;
+use serde::Deserialize;
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::str::FromStr;
 
-// 获取所有玩家名字列表
-pub async fn get_players(State(repo): State<LeagueRepository>) -> Json<Vec<String>> {
-    let players = repo.get_all_players().await;
-    let names = players.into_iter().map(|p| p.name).collect();
-    Json(names)
+// 读取可选的查询参数，存在但无法解析时返回 400
+fn parse_param<T: FromStr>(params: &HashMap<String, String>, key: &str) -> Result<Option<T>, AppError> {
+    match params.get(key) {
+        Some(value) => value
+            .parse::<T>()
+            .map(Some)
+            .map_err(|_| AppError::BadRequest(format!("无法解析参数 {}: {}", key, value))),
+        None => Ok(None),
+    }
+}
+
+// 按名字或曾用名查找玩家，不存在时返回 404
+async fn find_player(repo: &LeagueRepository, name: &str) -> Result<LeaguePlayer, AppError> {
+    match repo.get_player_by_name(name).await {
+        Ok(player) => Ok(player),
+        Err(sqlx::Error::RowNotFound) => Err(AppError::NotFound(format!("玩家 {} 不存在", name))),
+        Err(e) => Err(e.into()),
+    }
 }
 
-// 获取指定玩家的所有对战数据
-pub async fn get_player_matches(
+// 获取所有赛季编号，带group参数时只返回有该分组比赛的赛季
+pub async fn get_seasons(
     State(repo): State<LeagueRepository>,
-    Path(name): Path<String>,
-) -> Json<Vec<GameInfo>> {
-    let matches = repo.get_player_matches(&name).await;
-    Json(matches)
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<Vec<i32>>, AppError> {
+    if let Some(group) = params.get("group") {
+        return Ok(Json(repo.get_seasons_by_group(group).await?));
+    }
+    let seasons = repo.get_all_seasons().await?;
+    Ok(Json(seasons))
+}
+
+// 排行榜的排名依据，Less 表示 a 排在 b 前面
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RankKey {
+    Total,
+    Average,
+    Games,
+    AveragePosition,
+    Firsts,
+    Lasts,
+    Uma,
+    Penalty,
+}
+
+impl RankKey {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "total" => Some(RankKey::Total),
+            "average" => Some(RankKey::Average),
+            "games" => Some(RankKey::Games),
+            "average_position" => Some(RankKey::AveragePosition),
+            "firsts" => Some(RankKey::Firsts),
+            "lasts" => Some(RankKey::Lasts),
+            "uma" => Some(RankKey::Uma),
+            "penalty" => Some(RankKey::Penalty),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            RankKey::Total => "total",
+            RankKey::Average => "average",
+            RankKey::Games => "games",
+            RankKey::AveragePosition => "average_position",
+            RankKey::Firsts => "firsts",
+            RankKey::Lasts => "lasts",
+            RankKey::Uma => "uma",
+            RankKey::Penalty => "penalty",
+        }
+    }
+
+    fn compare(&self, a: &StandingRow, b: &StandingRow) -> Ordering {
+        match self {
+            RankKey::Total => b.total_points.total_cmp(&a.total_points),
+            RankKey::Average => b.average_points.total_cmp(&a.average_points),
+            RankKey::Games => b.games.cmp(&a.games),
+            // 平均顺位、四位次数和罚分越少越好
+            RankKey::AveragePosition => a.average_position.total_cmp(&b.average_position),
+            RankKey::Firsts => b.first_count.cmp(&a.first_count),
+            RankKey::Lasts => a.fourth_count.cmp(&b.fourth_count),
+            RankKey::Uma => b.uma_sum.total_cmp(&a.uma_sum),
+            RankKey::Penalty => a.penalty_sum.total_cmp(&b.penalty_sum),
+        }
+    }
+}
+
+// 依次比较排名依据和各个同分判定
+fn compare_standing(keys: &[RankKey], a: &StandingRow, b: &StandingRow) -> Ordering {
+    keys.iter()
+        .map(|key| key.compare(a, b))
+        .find(|o| o.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+// 达到最少比赛数的玩家按规则排名，完全同分时名次相同（1, 2, 2, 4）
+// 未达到的玩家排在后面，没有名次
+fn rank_standings(rows: &mut [StandingRow], keys: &[RankKey], min_games: i64) {
+    for row in rows.iter_mut() {
+        row.eligible = row.games >= min_games;
+    }
+    rows.sort_by(|a, b| {
+        b.eligible
+            .cmp(&a.eligible)
+            .then_with(|| compare_standing(keys, a, b))
+            .then_with(|| a.player_name.cmp(&b.player_name))
+    });
+    let mut rank = 0;
+    for i in 0..rows.len() {
+        if !rows[i].eligible {
+            break;
+        }
+        if i == 0 || compare_standing(keys, &rows[i - 1], &rows[i]).is_ne() {
+            rank = i + 1;
+        }
+        rows[i].rank = Some(rank);
+    }
+}
+
+#[derive(Deserialize)]
+pub struct StandingsParams {
+    // 参与排名所需的最少比赛数
+    #[serde(default)]
+    min_games: i64,
+    // total 或 average
+    rank_by: Option<String>,
+    // 逗号分隔的同分判定，依次比较
+    tie_breakers: Option<String>,
+    group: Option<String>,
+}
+
+// 赛季排行榜，默认按总分排名，同分时依次比较平均顺位和一位次数
+pub async fn get_season_standings(
+    State(repo): State<LeagueRepository>,
+    Path(season_num): Path<i32>,
+    Query(params): Query<StandingsParams>,
+) -> Result<Json<SeasonStandings>, AppError> {
+    let rank_by = match params.rank_by.as_deref().unwrap_or("total") {
+        "total" => RankKey::Total,
+        "average" => RankKey::Average,
+        other => return Err(AppError::BadRequest(format!("rank_by 只能是 total 或 average: {}", other))),
+    };
+    let tie_breakers = match params.tie_breakers.as_deref() {
+        Some(list) => list
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| {
+                RankKey::parse(s).ok_or_else(|| {
+                    AppError::BadRequest(format!(
+                        "无法识别的同分判定 {}，可选: total, average, games, average_position, firsts, lasts, uma, penalty",
+                        s
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        None => vec![RankKey::AveragePosition, RankKey::Firsts],
+    };
+    if params.min_games < 0 {
+        return Err(AppError::BadRequest("min_games 不能为负数".to_string()));
+    }
+
+    let mut standings = repo.get_season_standings(season_num, params.group.as_deref()).await?;
+    if standings.is_empty() {
+        let scope = match &params.group {
+            Some(group) => format!("赛季 {} 的分组 {}", season_num, group),
+            None => format!("赛季 {}", season_num),
+        };
+        return Err(AppError::NotFound(format!("{} 没有比赛记录", scope)));
+    }
+
+    let keys: Vec<RankKey> = std::iter::once(rank_by).chain(tie_breakers.iter().copied()).collect();
+    rank_standings(&mut standings, &keys, params.min_games);
+    Ok(Json(SeasonStandings {
+        season_num,
+        group_name: params.group,
+        rank_by: rank_by.as_str().to_string(),
+        min_games: params.min_games,
+        tie_breakers: tie_breakers.iter().map(|k| k.as_str().to_string()).collect(),
+        standings,
+    }))
+}
+
+const DEFAULT_MATCHES_PER_PAGE: usize = 100;
+const MAX_MATCHES_PER_PAGE: usize = 500;
+
+#[derive(Deserialize)]
+pub struct SeasonMatchesParams {
+    // date 或 table
+    sort: Option<String>,
+    // asc 或 desc
+    order: Option<String>,
+    // 从 1 开始的页码
+    page: Option<usize>,
+    per_page: Option<usize>,
 }
 
-// 获取所有赛季编号
-pub async fn get_seasons(State(repo): State<LeagueRepository>) -> Json<Vec<i32>> {
-    let seasons = repo.get_all_seasons().await;
-    Json(seasons)
+// 赛季全部比赛及四位玩家成绩，支持按日期或桌号排序和分页
+pub async fn get_season_matches(
+    State(repo): State<LeagueRepository>,
+    Path(season_num): Path<i32>,
+    Query(params): Query<SeasonMatchesParams>,
+) -> Result<Json<SeasonMatches>, AppError> {
+    let sort = params.sort.as_deref().unwrap_or("date");
+    if sort != "date" && sort != "table" {
+        return Err(AppError::BadRequest(format!("sort 只能是 date 或 table: {}", sort)));
+    }
+    let descending = match params.order.as_deref().unwrap_or("asc") {
+        "asc" => false,
+        "desc" => true,
+        other => return Err(AppError::BadRequest(format!("order 只能是 asc 或 desc: {}", other))),
+    };
+    let page = params.page.unwrap_or(1);
+    if page == 0 {
+        return Err(AppError::BadRequest("page 从 1 开始".to_string()));
+    }
+    let per_page = params.per_page.unwrap_or(DEFAULT_MATCHES_PER_PAGE);
+    if per_page == 0 || per_page > MAX_MATCHES_PER_PAGE {
+        return Err(AppError::BadRequest(format!("per_page 必须在 1 到 {} 之间", MAX_MATCHES_PER_PAGE)));
+    }
+
+    let mut games = repo.get_games_by_season(season_num).await?;
+    if games.is_empty() {
+        return Err(AppError::NotFound(format!("赛季 {} 没有比赛记录", season_num)));
+    }
+    // 旧记录没有 played_date 时按 game_time 的日期排序，同一天按桌号
+    games.sort_by(|a, b| {
+        let ordering = if sort == "date" {
+            let date_a = a.played_date.or(a.game_time.map(|dt| dt.date()));
+            let date_b = b.played_date.or(b.game_time.map(|dt| dt.date()));
+            date_a
+                .cmp(&date_b)
+                .then_with(|| a.game_time.cmp(&b.game_time))
+                .then_with(|| a.table_num.cmp(&b.table_num))
+        } else {
+            a.table_num.cmp(&b.table_num)
+        };
+        let ordering = ordering.then_with(|| a.id.cmp(&b.id));
+        if descending { ordering.reverse() } else { ordering }
+    });
+
+    let total = games.len();
+    let page_games: Vec<_> = games.into_iter().skip((page - 1) * per_page).take(per_page).collect();
+    let games = repo.build_game_infos(page_games).await?;
+    Ok(Json(SeasonMatches {
+        season_num,
+        sort: sort.to_string(),
+        order: if descending { "desc" } else { "asc" }.to_string(),
+        page,
+        per_page,
+        total,
+        games,
+    }))
 }
 
-// 支持赛季参数的玩家列表
+// 支持赛季和分组参数的玩家列表
 pub async fn get_players_by_season(
     State(repo): State<LeagueRepository>,
     Query(params): Query<HashMap<String, String>>,
-) -> Json<Vec<String>> {
-    if let Some(season) = params.get("season") {
-        if let Ok(season_num) = season.parse::<i32>() {
-            let players = repo.get_players_by_season(season_num).await;
-            let names = players.into_iter().map(|p| p.name).collect();
-            return Json(names);
-        }
-    }
-    // 无season参数时返回全部
-    let players = repo.get_all_players().await;
+) -> Result<Json<Vec<String>>, AppError> {
+    let season_num = parse_param::<i32>(&params, "season")?;
+    let players = if let Some(group) = params.get("group") {
+        repo.get_players_by_group(group, season_num).await?
+    } else if let Some(season_num) = season_num {
+        repo.get_players_by_season(season_num).await?
+    } else {
+        // 无season参数时返回全部
+        repo.list_players().await?
+    };
     let names = players.into_iter().map(|p| p.name).collect();
-    Json(names)
+    Ok(Json(names))
 }
 
-// 支持赛季参数的对战数据
+// 支持赛季和场次参数的对战数据
 pub async fn get_player_matches_by_season(
     State(repo): State<LeagueRepository>,
     Path(name): Path<String>,
     Query(params): Query<HashMap<String, String>>,
-) -> Json<Vec<GameInfo>> {
-    if let Some(season) = params.get("season") {
-        if let Ok(season_num) = season.parse::<i32>() {
-            let matches = repo.get_player_matches_by_season(&name, season_num).await;
-            return Json(matches);
-        }
+) -> Result<Json<Vec<GameInfo>>, AppError> {
+    let season_num = parse_param::<i32>(&params, "season")?;
+    let session_id = parse_param::<i32>(&params, "session")?;
+    let player = find_player(&repo, &name).await?;
+    let matches = match (session_id, season_num) {
+        (Some(session_id), _) => repo.get_player_matches_by_session(player.id, session_id, season_num).await?,
+        (None, Some(season_num)) => repo.get_player_matches_by_season(player.id, season_num).await?,
+        (None, None) => repo.get_player_matches(player.id).await?,
+    };
+    Ok(Json(matches))
+}
+
+// 玩家成绩汇总，带season参数时只统计该赛季
+pub async fn get_player_summary(
+    State(repo): State<LeagueRepository>,
+    Path(name): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<PlayerSummary>, AppError> {
+    let season_num = parse_param::<i32>(&params, "season")?;
+    let player = find_player(&repo, &name).await?;
+    Ok(Json(repo.get_player_summary(player, season_num).await?))
+}
+
+// 获取所有场次
+pub async fn get_sessions(State(repo): State<LeagueRepository>) -> Result<Json<Vec<LeagueSession>>, AppError> {
+    Ok(Json(repo.list_sessions().await?))
+}
+
+// 获取单个场次及其所有比赛
+pub async fn get_session(
+    State(repo): State<LeagueRepository>,
+    Path(sid): Path<i32>,
+) -> Result<Json<SessionInfo>, AppError> {
+    let session = repo
+        .get_session(sid)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("场次 {} 不存在", sid)))?;
+    let games = repo.get_session_games(sid).await?;
+    Ok(Json(SessionInfo { session, games }))
+}
+
+#[derive(Deserialize)]
+pub struct MergeParams {
+    into: i32,
+}
+
+// 把玩家 id 合并到 into：转移成绩、座位和曾用名后删除 id，原名字仍可查询
+pub async fn merge_players(
+    State(repo): State<LeagueRepository>,
+    Path(id): Path<i32>,
+    Query(params): Query<MergeParams>,
+) -> Result<Json<PlayerMerge>, AppError> {
+    if id == params.into {
+        return Err(AppError::BadRequest("不能把玩家合并到自己".to_string()));
+    }
+    // 同步会按上游 pid 写入玩家，合并需等同步结束。
+    // 合并事务提交前一直持有同步状态锁，期间新的同步要等锁释放后才能开始
+    let sync_state = SYNC_STATE.lock().await;
+    if sync_state.is_running {
+        return Err(AppError::Conflict("同步正在进行，请稍后再合并".to_string()));
     }
-    let matches = repo.get_player_matches(&name).await;
-    Json(matches)
+
+    let mut tx = repo.begin().await?;
+    let from = tx
+        .get_player(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("玩家 {} 不存在", id)))?;
+    let into = tx
+        .get_player(params.into)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("玩家 {} 不存在", params.into)))?;
+
+    let shared = tx.count_shared_tables(from.id, into.id).await?;
+    if shared > 0 {
+        return Err(AppError::Conflict(format!(
+            "玩家 {} 和 {} 在 {} 场比赛中同桌，不能合并",
+            from.name, into.name, shared
+        )));
+    }
+
+    let merge = tx.merge_players(from, into).await?;
+    tx.commit().await?;
+    drop(sync_state);
+    println!(
+        "✅ 已将玩家 {} (ID {}) 合并到 {} (ID {})：转移 {} 条成绩，更新 {} 张比赛桌",
+        merge.from.name, merge.from.id, merge.into.name, merge.into.id, merge.results_moved, merge.tables_updated
+    );
+    Ok(Json(merge))
 }
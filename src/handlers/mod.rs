@@ -2,4 +2,4 @@ pub mod sync;
 mod league_api;
 
 pub use sync::sync_trigger;
-pub use league_api::{get_seasons, get_players_by_season, get_player_matches_by_season};
+pub use league_api::{get_seasons, get_players_by_season, get_player_matches_by_season, get_sessions, get_session, merge_players, get_season_standings, get_season_matches, get_player_summary};
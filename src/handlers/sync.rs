@@ -1,493 +1,305 @@
 use axum::{
-    extract::{Query, State},
-    http::StatusCode,
-    response::{IntoResponse, Response},
+    extract::{rejection::JsonRejection, Path, Query, State},
+    http::{header, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    Json,
 };
+use futures_util::stream::{self, Stream};
 use serde::Deserialize;
+use crate::config::{ReconcileMode, SourceConfig, SYNC_CONFIG};
 use crate::db::LeagueRepository;
-use crate::models::league::{GameInfo, PlayerResult, LeaguePlayer, LeagueGame, LeagueResult};
-use std::sync::Arc;
-use tokio::sync::Mutex;
-use chrono::NaiveDate;
-use regex::Regex;
+use crate::error::AppError;
+use crate::models::sync::{QuarantinedGame, SyncRun, SyncSnapshot, WebhookDelivery};
+use crate::sync::{self, DataRoot, SyncError, SyncJob, SyncMode, SyncTrigger, SYNC_STATE};
+use crate::sync::job::{self, JobEvent, JobProgress};
+use crate::sync::{description, diff, reconcile, snapshot};
+use crate::sync::source::MemorySource;
 use std::collections::HashMap;
+use tokio::sync::broadcast::{error::RecvError, Receiver};
 
-pub async fn hello() -> impl IntoResponse {
-    (StatusCode::OK, "Hello, World!")
-}
+// 上传导入在运行记录中使用的数据源名称
+const UPLOAD_LABEL: &str = "upload";
+
+// 上传的 data.json 可能较大，单独放宽请求体大小限制
+pub const UPLOAD_BODY_LIMIT: usize = 32 * 1024 * 1024;
 
 // 定义查询参数结构体
 #[derive(Deserialize)]
 pub struct SyncParams {
     force: Option<String>,
+    source: Option<String>,
+    // json（默认）、html 或 backfill
+    #[serde(default)]
+    mode: SyncMode,
+    // dry run 的输出格式：json（默认）或 text
+    format: Option<String>,
+}
+
+// 根据 source 参数选择数据源，未指定时使用默认数据源，并检查数据源是否支持所选模式
+fn resolve_source(params: &SyncParams) -> Result<&'static SourceConfig, AppError> {
+    let source = SYNC_CONFIG.source(params.source.as_deref()).ok_or_else(|| {
+        AppError::BadRequest(format!("未知的数据源: {}", params.source.as_deref().unwrap_or("")))
+    })?;
+    params.mode.check_source(source).map_err(AppError::BadRequest)?;
+    Ok(source)
 }
 
 // 更新 sync_trigger 函数，接收LeagueRepository作为状态
 pub async fn sync_trigger(
     State(repo): State<LeagueRepository>,
     Query(params): Query<SyncParams>
-) -> Response {
-    if let Some(force) = &params.force {
-        if force == "true" {
-            return force_sync(repo).await;
-        }
+) -> Result<Response, AppError> {
+    let source = resolve_source(&params)?;
+
+    if params.force.as_deref() == Some("true") {
+        return force_sync(repo, source, params.mode).await;
     }
 
     // 返回成功信息
-    (StatusCode::OK, "同步触发成功").into_response()
+    Ok((StatusCode::OK, "同步触发成功").into_response())
 }
 
-// 定义同步状态结构体
-struct SyncState {
-    is_running: bool,
-    current_id: usize,
-    success_count: usize,
+// 在后台启动同步任务，立即返回任务ID
+async fn force_sync(repo: LeagueRepository, source: &SourceConfig, mode: SyncMode) -> Result<Response, AppError> {
+    spawn_job(sync::start_sync(&repo, source, mode, SyncTrigger::Manual).await)
 }
 
-// 全局共享状态
-lazy_static::lazy_static! {
-    static ref SYNC_STATE: Arc<Mutex<SyncState>> = Arc::new(Mutex::new(SyncState {
-        is_running: false,
-        current_id: 0,
-        success_count: 0,
-    }));
-}
-
-// 修改force_sync函数以接收LeagueRepository
-async fn force_sync(repo: LeagueRepository) -> Response {
-    // 尝试获取锁并检查是否已在运行
-    let mut state = SYNC_STATE.lock().await;
-
-    if state.is_running {
-        // 如果已经在运行，返回当前状态
-        let message = format!(
-            "强制同步正在进行中，当前处理ID: {}，已成功处理: {} 个请求",
-            state.current_id, state.success_count
-        );
-        println!("{}", message);
-        return (StatusCode::OK, message).into_response();
-    }
-
-    // 标记为正在运行
-    state.is_running = true;
-    state.current_id = 0;
-    state.success_count = 0;
-
-    // 释放锁，这样其他请求可以查询状态
-    drop(state);
-
-    // 创建HTTP客户端
-    let client = reqwest::Client::new();
-    let url = "https://mahjong.chaotic.quest/sthlm-meetups-league/data.json";
-
-    println!("开始强制同步过程，拉取JSON数据...");
-
-    // 拉取JSON数据
-    let resp = match client.get(url).send().await {
-        Ok(r) => r,
-        Err(e) => {
-            let msg = format!("请求数据源失败: {}", e);
-            println!("{}", msg);
-            let mut state = SYNC_STATE.lock().await;
-            state.is_running = false;
-            return (StatusCode::INTERNAL_SERVER_ERROR, msg).into_response();
-        }
-    };
-    let data: DataRoot = match resp.json().await {
-        Ok(d) => d,
-        Err(e) => {
-            let msg = format!("解析JSON失败: {}", e);
-            println!("{}", msg);
-            let mut state = SYNC_STATE.lock().await;
-            state.is_running = false;
-            return (StatusCode::INTERNAL_SERVER_ERROR, msg).into_response();
+fn spawn_job(job: Result<SyncJob, SyncError>) -> Result<Response, AppError> {
+    let job = job.inspect_err(|err| {
+        // 如果已经在运行，返回正在运行的任务
+        if let SyncError::AlreadyRunning { .. } = err {
+            println!("{}", err);
         }
-    };
-
-    // 构建pid到玩家名映射
-    let mut pid_name_map = HashMap::new();
-    for p in &data.collection.players {
-        pid_name_map.insert(p.pid, p.name.clone());
-    }
-
-    let mut success_count = 0;
-    let mut saved_count = 0;
-
-    // 获取现有玩家
-    let existing_players = repo.list_players().await.unwrap_or_default();
+    })?;
+    let job_id = job.id;
+    tokio::spawn(job.run());
+    let body = serde_json::json!({
+        "job_id": job_id,
+        "status_url": format!("/sync/jobs/{}", job_id),
+        "events_url": format!("/sync/jobs/{}/events", job_id),
+    });
+    Ok((StatusCode::ACCEPTED, Json(body)).into_response())
+}
 
-    // 第一步：检查缺失的ID和不一致的姓名
-    println!("开始检查玩家数据一致性...");
+// 请求取消正在运行的同步，任务在下一场比赛前停止并回滚
+pub async fn cancel_sync() -> Result<Response, AppError> {
+    let job_id = sync::request_cancel()
+        .await
+        .ok_or_else(|| AppError::Conflict("没有正在运行的同步".to_string()))?;
+    println!("已请求取消同步任务 {}", job_id);
+    let body = serde_json::json!({
+        "job_id": job_id,
+        "message": "已请求取消，任务将在下一场比赛前停止并回滚",
+        "status_url": format!("/sync/jobs/{}", job_id),
+    });
+    Ok((StatusCode::ACCEPTED, Json(body)).into_response())
+}
 
-    // 构建现有玩家的ID到姓名映射
-    let mut existing_id_name_map = HashMap::new();
-    for player in &existing_players {
-        existing_id_name_map.insert(player.id, player.name.clone());
-    }
+// 查询同步任务的实时进度
+pub async fn get_sync_job(Path(id): Path<i32>) -> Result<Json<JobProgress>, AppError> {
+    job::progress(id).await.map(Json).ok_or_else(|| {
+        AppError::NotFound(format!("同步任务 {} 不存在或已过期，请查询 /sync/runs/{}", id, id))
+    })
+}
 
-    let mut updated_players_count = 0;
-    let mut created_players_count = 0;
-
-    // 检查JSON中的每个玩家
-    for json_player in &data.collection.players {
-        let pid = json_player.pid as i32;
-        let json_name = &json_player.name;
-
-        if let Some(existing_name) = existing_id_name_map.get(&pid) {
-            // ID存在，检查姓名是否一致
-            if existing_name != json_name {
-                println!("⚠️  发现ID {} 的姓名不一致：", pid);
-                println!("   数据库中: '{}'", existing_name);
-                println!("   JSON中:   '{}'", json_name);
-                println!("   正在更新...");
-
-                let update_player = LeaguePlayer::new(pid, json_name.clone());
-                match repo.update_player(&update_player).await {
-                    Ok(_) => {
-                        println!("✅ 成功更新ID {} 的玩家姓名: '{}' -> '{}'", pid, existing_name, json_name);
-                        // 更新本地映射
-                        existing_id_name_map.insert(pid, json_name.clone());
-                        updated_players_count += 1;
-                    },
-                    Err(e) => {
-                        println!("❌ 更新玩家ID {} 姓名失败: {}", pid, e);
-                    }
+// 以SSE推送同步任务的进度和警告，任务结束后关闭连接
+pub async fn sync_job_events(
+    Path(id): Path<i32>,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, AppError> {
+    let (snapshot, rx) = job::subscribe(id)
+        .await
+        .ok_or_else(|| AppError::NotFound(format!("同步任务 {} 不存在或已过期", id)))?;
+
+    // 先推送当前进度，再转发后续事件
+    let initial = JobEvent::Progress(snapshot);
+    let stream = stream::unfold(Some((Some(initial), rx)), |state| async move {
+        let (pending, mut rx): (Option<JobEvent>, Receiver<JobEvent>) = state?;
+        let event = match pending {
+            Some(event) => event,
+            None => loop {
+                match rx.recv().await {
+                    Ok(event) => break event,
+                    // 订阅者处理过慢时跳过丢失的事件
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return None,
                 }
-            } else {
-                println!("✓ ID {} 的玩家 '{}' 信息一致", pid, json_name);
-            }
-        } else {
-            // ID不存在，需要创建新玩家
-            println!("📝 发现缺失的玩家ID {}，姓名: {}，正在创建...", pid, json_name);
-            let new_player = LeaguePlayer::new(pid, json_name.clone());
-            match repo.create_player_with_id(&new_player).await {
-                Ok(created_id) => {
-                    println!("✅ 成功创建玩家: {} (ID: {})", json_name, created_id);
-                    existing_id_name_map.insert(pid, json_name.clone());
-                    created_players_count += 1;
-                },
-                Err(e) => {
-                    println!("❌ 创建玩家ID {} ({}) 失败: {}", pid, json_name, e);
-                }
-            }
-        }
-    }
+            },
+        };
+        let next = if event.is_terminal() { None } else { Some((None, rx)) };
+        Some((to_sse_event(&event), next))
+    });
 
-    println!("玩家数据一致性检查完成:");
-    println!("  - 更新了 {} 个玩家的姓名", updated_players_count);
-    println!("  - 创建了 {} 个新玩家", created_players_count);
-    println!("  - 总共检查了 {} 个玩家", data.collection.players.len());
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
 
-    // 构建姓名到ID的映射，用于后续的游戏处理
-    let mut player_id_map = HashMap::new();
-    for (id, name) in &existing_id_name_map {
-        player_id_map.insert(name.clone(), *id);
+fn to_sse_event(event: &JobEvent) -> Result<Event, axum::Error> {
+    let sse = Event::default().event(event.name());
+    match event {
+        JobEvent::Progress(progress) => sse.json_data(progress),
+        JobEvent::Warning(message) => sse.json_data(serde_json::json!({ "message": message })),
     }
+}
 
-    for game in &data.collection.games {
-        // 生成PlayerResult列表
-        let mut player_results = Vec::new();
-        for result in &game.results {
-            let player_name = pid_name_map.get(&result.player).cloned().unwrap_or_else(|| "Unknown".to_string());
-            let seat = result.seat.clone();
-            let score = result.result;
-            let position = result.position.unwrap_or(0) as i32;
-            let uma = result.uma.unwrap_or(0.0);
-            let penalty = result.penalty.unwrap_or(0.0);
-            let total = result.total.unwrap_or(0.0);
-            player_results.push(PlayerResult {
-                seat,
-                player_name,
-                score,
-                position,
-                uma,
-                penalty,
-                total,
-            });
-        }
-
-        // 优化桌号提取逻辑，兼容多种描述格式，失败时用gid兜底
-        let mut season_num = 0;
-        let mut table_num = 0;
-        let desc = &game.description;
-        // 支持多种格式：Season X: Table Y 或 Season X: Group ...: Table Y
-        let re = Regex::new(r"Season (\d+)(?:: [^:]+)*: Table (\d+)").unwrap();
-        if let Some(caps) = re.captures(desc) {
-            season_num = caps.get(1).and_then(|m| m.as_str().parse::<i32>().ok()).unwrap_or(0);
-            table_num = caps.get(2).and_then(|m| m.as_str().parse::<i32>().ok()).unwrap_or(game.gid as i32);
-        } else {
-            // fallback: 尝试简单的 Season X 格式
-            let simple_re = Regex::new(r"Season (\d+)").unwrap();
-            if let Some(caps) = simple_re.captures(desc) {
-                season_num = caps.get(1).and_then(|m| m.as_str().parse::<i32>().ok()).unwrap_or(0);
-            }
-            table_num = game.gid as i32;
-        }
+#[derive(Deserialize)]
+pub struct SyncRunsParams {
+    limit: Option<i64>,
+}
 
-        // 创建GameInfo对象
-        let game_info = GameInfo {
-            game_id: game.gid as i32,
-            played_date: NaiveDate::parse_from_str(&game.played, "%Y-%m-%d").unwrap_or_else(|_| NaiveDate::from_ymd_opt(1970,1,1).unwrap()),
-            registered: None,
-            description: game.description.clone(),
-            processed: true,
-            player_results,
-            season_num,
-            table_num,
-        };
+// 列出最近的同步运行记录，默认50条
+pub async fn list_sync_runs(
+    State(repo): State<LeagueRepository>,
+    Query(params): Query<SyncRunsParams>,
+) -> Result<Json<Vec<SyncRun>>, AppError> {
+    let limit = params.limit.unwrap_or(50).clamp(1, 500);
+    Ok(Json(repo.list_sync_runs(limit).await?))
+}
 
-        // 步骤1：首先获取所有现有玩家，以便正确分配新ID
-        for player_result in &game_info.player_results {
-            let player_name = &player_result.player_name;
-            if player_id_map.contains_key(player_name) {
-                continue;
-            }
+// 查询单条同步运行记录
+pub async fn get_sync_run(
+    State(repo): State<LeagueRepository>,
+    Path(id): Path<i32>,
+) -> Result<Json<SyncRun>, AppError> {
+    repo.get_sync_run(id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound(format!("同步记录 {} 不存在", id)))
+}
 
-            // 查找该玩家在JSON数据中的pid
-            let player_pid = data.collection.players.iter()
-                .find(|p| p.name == *player_name)
-                .map(|p| p.pid as i32)
-                .unwrap_or(-1);
-
-            if player_pid != -1 {
-                // 使用JSON中的pid作为数据库ID创建玩家
-                let new_player = LeaguePlayer::new(player_pid, player_name.clone());
-                match repo.create_player_with_id(&new_player).await {
-                    Ok(created_id) => {
-                        println!("创建新玩家: {} (ID: {}, 来自JSON pid: {})", player_name, created_id, player_pid);
-                        player_id_map.insert(player_name.clone(), player_pid);
-                    },
-                    Err(e) => {
-                        // 检查是否是ID冲突错误
-                        if e.to_string().contains("duplicate key") || e.to_string().contains("unique constraint") {
-                            println!("ID {} 已存在，更新该ID对应的玩家名字为: {}", player_pid, player_name);
-                            // ID冲突时，更新已存在ID的玩家名字
-                            let update_player = LeaguePlayer::new(player_pid, player_name.clone());
-                            match repo.update_player(&update_player).await {
-                                Ok(_) => {
-                                    println!("成功更新ID {} 的玩家名字为: {}", player_pid, player_name);
-                                    player_id_map.insert(player_name.clone(), player_pid);
-                                },
-                                Err(update_e) => {
-                                    println!("更新玩家 {} (ID: {}) 失败: {}，尝试自动分配ID", player_name, player_pid, update_e);
-                                    // 如果更新也失败，则使用自动分配ID的方式
-                                    let fallback_player = LeaguePlayer::new(-1, player_name.clone());
-                                    if let Ok(new_player_id) = repo.create_player(&fallback_player).await {
-                                        println!("创建新玩家 {} 成功 (自动分配ID: {})", player_name, new_player_id);
-                                        player_id_map.insert(player_name.clone(), new_player_id);
-                                    }
-                                }
-                            }
-                        } else {
-                            println!("使用指定ID创建玩家 {} 失败: {}，尝试自动分配ID", player_name, e);
-                            // 其他错误，使用自动分配ID的方式
-                            let fallback_player = LeaguePlayer::new(-1, player_name.clone());
-                            if let Ok(new_player_id) = repo.create_player(&fallback_player).await {
-                                println!("创建新玩家 {} 成功 (自动分配ID: {})", player_name, new_player_id);
-                                player_id_map.insert(player_name.clone(), new_player_id);
-                            }
-                        }
-                    }
-                }
-            } else {
-                println!("警告: 玩家 {} 在JSON数据中找不到对应的pid，使用自动分配ID", player_name);
-                let fallback_player = LeaguePlayer::new(-1, player_name.clone());
-                if let Ok(new_player_id) = repo.create_player(&fallback_player).await {
-                    println!("创建新玩家 {} 成功 (自动分配ID: {})", player_name, new_player_id);
-                    player_id_map.insert(player_name.clone(), new_player_id);
-                }
-            }
-        }
+#[derive(Deserialize)]
+pub struct QuarantineParams {
+    all: Option<bool>,
+}
 
-        // 步骤2：创建/更新游戏记录，使用已获取的玩家ID
-        let mut e_id = 0;
-        let mut s_id = 0;
-        let mut w_id = 0;
-        let mut n_id = 0;
-        for player_result in &game_info.player_results {
-            if let Some(&player_id) = player_id_map.get(&player_result.player_name) {
-                // 统一seat匹配，去除括号并大写
-                let seat = player_result.seat.trim_matches(|c| c == '[' || c == ']').to_uppercase();
-                match seat.as_str() {
-                    "E" | "EAST" => e_id = player_id,
-                    "S" | "SOUTH" => s_id = player_id,
-                    "W" | "WEST" => w_id = player_id,
-                    "N" | "NORTH" => n_id = player_id,
-                    _ => {}
-                }
-            }
-        }
+// 列出隔离中的比赛，all=true 时包括已放行的
+pub async fn list_quarantine(
+    State(repo): State<LeagueRepository>,
+    Query(params): Query<QuarantineParams>,
+) -> Result<Json<Vec<QuarantinedGame>>, AppError> {
+    Ok(Json(repo.list_quarantine(params.all.unwrap_or(false)).await?))
+}
 
-        let mut game_db = LeagueGame::new(
-            game_info.registered,
-            game_info.season_num,
-            game_info.table_num,
-            game_info.processed,
-            game_info.game_id,
-            e_id,
-            s_id,
-            w_id,
-            n_id,
-        );
+// 放行隔离中的比赛，下次同步时不经校验直接写入
+pub async fn release_quarantine(
+    State(repo): State<LeagueRepository>,
+    Path(gid): Path<i32>,
+) -> Result<Json<QuarantinedGame>, AppError> {
+    let game = repo
+        .release_quarantine(gid)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("游戏 gid {} 不在隔离表中", gid)))?;
+    println!("✅ 已放行隔离中的游戏 gid {}，下次同步时写入", gid);
+    Ok(Json(game))
+}
 
-        let existing_game = repo.get_game_by_season_and_table(game_info.season_num, game_info.table_num).await;
-        match existing_game {
-            Ok(existing_game) => {
-                println!("发现相同赛季({})和桌号({})的游戏记录，ID: {}，将进行更新",
-                         game_info.season_num, game_info.table_num, existing_game.id);
-                game_db = LeagueGame::new(
-                    game_info.registered,
-                    game_info.season_num,
-                    game_info.table_num,
-                    game_info.processed,
-                    existing_game.id,
-                    e_id,
-                    s_id,
-                    w_id,
-                    n_id,
-                );
-                let _ = repo.update_game(&game_db).await;
-            },
-            Err(_) => {
-                if let Ok(new_game_id) = repo.create_game(&game_db).await {
-                    println!("游戏保存成功: ID {}", new_game_id);
-                    saved_count += 1;
-                    game_db.id = new_game_id;
-                }
-            }
-        }
+// 查询同步状态，包括最近一次定时同步的结果
+pub async fn sync_status() -> Json<serde_json::Value> {
+    let state = SYNC_STATE.lock().await;
+    Json(serde_json::json!(*state))
+}
 
-        // 步骤3：创建/更新玩家成绩
-        if game_db.id >= 0 {
-            for result in &game_info.player_results {
-                let player_id = {
-                    let seat = result.seat.trim_matches(|c| c == '[' || c == ']').to_uppercase();
-                    match seat.as_str() {
-                        "E" | "EAST" => game_db.e,
-                        "S" | "SOUTH" => game_db.s,
-                        "W" | "WEST" => game_db.w,
-                        "N" | "NORTH" => game_db.n,
-                        _ => continue,
-                    }
-                };
-
-                let game_result = LeagueResult::new(
-                    0,
-                    game_db.id,
-                    player_id,
-                    result.score,
-                    result.position,
-                    result.uma,
-                    result.penalty,
-                    result.total
-                );
-
-                match repo.get_result_by_table_and_player(game_db.id, player_id).await {
-                    Ok(mut existing_result) => {
-                        existing_result.result = result.score;
-                        existing_result.position = result.position;
-                        existing_result.uma = result.uma;
-                        existing_result.penalty = result.penalty;
-                        existing_result.total = result.total;
-                        let _ = repo.update_result(&existing_result).await;
-                    },
-                    Err(_) => {
-                        let _ = repo.create_result(&game_result).await;
-                    }
-                }
-            }
-        }
+// dry run: 只返回将要同步的比赛和玩家信息，不写数据库
+pub async fn dry_run_sync(
+    State(repo): State<LeagueRepository>,
+    Query(params): Query<SyncParams>,
+) -> Result<Response, AppError> {
+    let source = resolve_source(&params)?;
+    // 数据源本身的问题返回502，本地问题返回500
+    let data = sync::preview_data(&repo, source, params.mode).await?;
+    let reconcile = reconcile::effective_mode(params.mode);
+    preview(&repo, &data, &source.name, params.mode.as_str(), reconcile, params.format.as_deref()).await
+}
 
-        success_count += 1;
-        // 更新状态
-        let mut state = SYNC_STATE.lock().await;
-        state.current_id = game.gid;
-        state.success_count = success_count;
-    }
+#[derive(Deserialize)]
+pub struct ImportParams {
+    #[serde(default)]
+    dry_run: bool,
+    // dry run 的输出格式：json（默认）或 text
+    format: Option<String>,
+}
 
-    // 同步完成，重置状态
-    {
-        let mut state = SYNC_STATE.lock().await;
-        state.is_running = false;
+// 导入上传的 data.json，dry_run=true 时只返回预览
+pub async fn upload_sync(
+    State(repo): State<LeagueRepository>,
+    Query(params): Query<ImportParams>,
+    payload: Result<Json<DataRoot>, JsonRejection>,
+) -> Result<Response, AppError> {
+    let Json(data) = payload
+        .map_err(|e| AppError::BadRequest(format!("上传的数据不是有效的 data.json: {}", e.body_text())))?;
+    if params.dry_run {
+        return preview(&repo, &data, UPLOAD_LABEL, UPLOAD_LABEL, ReconcileMode::Off, params.format.as_deref()).await;
     }
-    println!("强制同步完成，成功处理数: {}，成功保存数: {}", success_count, saved_count);
-    (StatusCode::OK, format!("强制同步触发成功，共处理{}场比赛，成功保存{}条记录", success_count, saved_count)).into_response()
+    spawn_job(sync::start_import(&repo, MemorySource::new(UPLOAD_LABEL, data), SyncTrigger::Upload).await)
 }
 
-// 适配 data.json 的结构体 - 仅用于JSON反序列化
-#[derive(Debug, Deserialize)]
-struct DataRoot {
-    collection: DataCollection,
+#[derive(Deserialize)]
+pub struct SnapshotsParams {
+    source: Option<String>,
+    limit: Option<i64>,
 }
 
-#[derive(Debug, Deserialize)]
-struct DataCollection {
-    players: Vec<DataPlayer>,
-    games: Vec<DataGame>,
-    #[allow(dead_code)]
-    sessions: Vec<DataSession>,
+// 列出存档的快照，默认50条
+pub async fn list_snapshots(
+    State(repo): State<LeagueRepository>,
+    Query(params): Query<SnapshotsParams>,
+) -> Result<Json<Vec<SyncSnapshot>>, AppError> {
+    let limit = params.limit.unwrap_or(50).clamp(1, 500);
+    Ok(Json(repo.list_snapshots(params.source.as_deref(), limit).await?))
 }
 
-#[derive(Debug, Deserialize)]
-struct DataPlayer {
-    pid: usize,
-    name: String,
+#[derive(Deserialize)]
+pub struct DeliveriesParams {
+    limit: Option<i64>,
 }
 
-#[derive(Debug, Deserialize)]
-struct DataGame {
-    gid: usize,
-    played: String,
-    description: String,
-    #[allow(dead_code)]
-    players: Vec<usize>,
-    results: Vec<DataResult>,
+// 列出webhook投递记录，默认50条
+pub async fn list_webhook_deliveries(
+    State(repo): State<LeagueRepository>,
+    Query(params): Query<DeliveriesParams>,
+) -> Result<Json<Vec<WebhookDelivery>>, AppError> {
+    let limit = params.limit.unwrap_or(50).clamp(1, 500);
+    Ok(Json(repo.list_webhook_deliveries(limit).await?))
 }
 
-#[derive(Debug, Deserialize)]
-struct DataResult {
-    player: usize,
-    result: f64,
-    seat: String,
-    uma: Option<f64>,
-    position: Option<u8>,
-    penalty: Option<f64>,
-    total: Option<f64>,
+// 返回快照解压后的原始 data.json
+pub async fn get_snapshot(State(repo): State<LeagueRepository>, Path(id): Path<i32>) -> Result<Response, AppError> {
+    let body = snapshot::load_raw(&repo, id)
+        .await
+        .map_err(AppError::Internal)?
+        .ok_or_else(|| AppError::NotFound(format!("快照 {} 不存在", id)))?;
+    Ok(([(header::CONTENT_TYPE, "application/json")], body).into_response())
 }
 
-#[derive(Debug, Deserialize)]
-struct DataSession {
-    #[allow(dead_code)]
-    sid: usize,
-    #[allow(dead_code)]
-    name: String,
-    #[allow(dead_code)]
-    group: String,
-    #[allow(dead_code)]
-    date: String,
-    #[allow(dead_code)]
-    games: Vec<usize>,
+// 从存档的快照重新同步，dry_run=true 时只返回预览
+pub async fn replay_snapshot(
+    State(repo): State<LeagueRepository>,
+    Path(id): Path<i32>,
+    Query(params): Query<ImportParams>,
+) -> Result<Response, AppError> {
+    let not_found = || AppError::NotFound(format!("快照 {} 不存在", id));
+    let snapshot = repo.get_snapshot(id).await?.ok_or_else(not_found)?;
+    let data = snapshot::load(&repo, id)
+        .await
+        .map_err(AppError::Internal)?
+        .ok_or_else(not_found)?;
+    if params.dry_run {
+        let mode = format!("snapshot:{}", id);
+        return preview(&repo, &data, &snapshot.source, &mode, ReconcileMode::Off, params.format.as_deref()).await;
+    }
+    spawn_job(sync::start_replay(&repo, &snapshot.source, id, data).await)
 }
 
-// dry run: 只返回将要同步的比赛和玩家信息，不写数据库
-pub async fn dry_run_sync(State(repo): State<LeagueRepository>) -> Response {
-    let client = reqwest::Client::new();
-    let url = "https://mahjong.chaotic.quest/sthlm-meetups-league/data.json";
-    let resp = match client.get(url).send().await {
-        Ok(r) => r,
-        Err(e) => {
-            let msg = format!("请求数据源失败: {}", e);
-            return (StatusCode::INTERNAL_SERVER_ERROR, msg).into_response();
-        }
-    };
-    let data: DataRoot = match resp.json().await {
-        Ok(d) => d,
-        Err(e) => {
-            let msg = format!("解析JSON失败: {}", e);
-            return (StatusCode::INTERNAL_SERVER_ERROR, msg).into_response();
-        }
-    };
-
+// 生成同步预览：逐场检查座位和玩家，并与数据库现状对比
+async fn preview(
+    repo: &LeagueRepository,
+    data: &DataRoot,
+    source: &str,
+    mode: &str,
+    reconcile: ReconcileMode,
+    format: Option<&str>,
+) -> Result<Response, AppError> {
     // 构建pid到玩家名映射
     let mut pid_name_map = HashMap::new();
     for p in &data.collection.players {
@@ -495,7 +307,7 @@ pub async fn dry_run_sync(State(repo): State<LeagueRepository>) -> Response {
     }
 
     // 获取现有玩家
-    let existing_players = repo.list_players().await.unwrap_or_default();
+    let existing_players = repo.list_players().await?;
     let mut existing_player_names = std::collections::HashSet::new();
     for player in &existing_players {
         existing_player_names.insert(player.name.clone());
@@ -505,25 +317,14 @@ pub async fn dry_run_sync(State(repo): State<LeagueRepository>) -> Response {
     let mut all_players = std::collections::BTreeSet::new();
     let mut new_players = std::collections::BTreeSet::new();
     let mut games_info = Vec::new();
-    let mut warnings = Vec::new();
+    let mut warnings = data.warnings.clone();
     let mut season_stats = std::collections::HashMap::new();
 
     for game in &data.collection.games {
-        // 解析赛季和桌号
-        let mut season_num = 0;
-        let mut table_num = 0;
-        let desc = &game.description;
-        let re = Regex::new(r"Season (\d+)(?:: [^:]+)*: Table (\d+)").unwrap();
-        if let Some(caps) = re.captures(desc) {
-            season_num = caps.get(1).and_then(|m| m.as_str().parse::<i32>().ok()).unwrap_or(0);
-            table_num = caps.get(2).and_then(|m| m.as_str().parse::<i32>().ok()).unwrap_or(game.gid as i32);
-        } else {
-            let simple_re = Regex::new(r"Season (\d+)").unwrap();
-            if let Some(caps) = simple_re.captures(desc) {
-                season_num = caps.get(1).and_then(|m| m.as_str().parse::<i32>().ok()).unwrap_or(0);
-            }
-            table_num = game.gid as i32;
-        }
+        // 解析赛季、分组和桌号
+        let (parsed, warning) = description::parse_or_fallback(&game.description, game.gid);
+        warnings.extend(warning);
+        let (season_num, table_num) = (parsed.season_num, parsed.table_num);
 
         // 统计赛季信息
         *season_stats.entry(season_num).or_insert(0) += 1;
@@ -590,21 +391,52 @@ pub async fn dry_run_sync(State(repo): State<LeagueRepository>) -> Response {
         games_info.push(serde_json::json!({
             "gid": game.gid,
             "played": game.played,
+            "registered": game.registered,
             "description": game.description,
             "season_num": season_num,
+            "group_name": parsed.group,
             "table_num": table_num,
             "players": game_players,
             "seat_assignment": seat_assignment
         }));
     }
 
+    // 与数据库现状对比生成差异报告，只读，事务最后回滚
+    let mut tx = repo.begin().await?;
+    let diff = diff::build_diff(&mut tx, data, reconcile).await;
+    let _ = tx.rollback().await;
+    let diff = diff?;
+
+    if format == Some("text") {
+        let mut text = format!(
+            "数据源 {} 同步预览（模式 {}），共 {} 场比赛，警告 {} 条\n",
+            source,
+            mode,
+            data.collection.games.len(),
+            warnings.len()
+        );
+        text.push_str(&diff.to_text());
+        for warning in &warnings {
+            text.push_str(&format!("警告: {}\n", warning));
+        }
+        return Ok((StatusCode::OK, text).into_response());
+    }
+
     let result = serde_json::json!({
+        "source": source,
+        "mode": mode,
         "summary": {
             "total_games": data.collection.games.len(),
             "total_players": all_players.len(),
             "new_players_count": new_players.len(),
             "existing_players_count": all_players.len() - new_players.len(),
             "warnings_count": warnings.len(),
+            "tables_created_count": diff.tables_created.len(),
+            "results_changed_count": diff.results_changed.len(),
+            "players_renamed_count": diff.players_renamed.len(),
+            "quarantined_count": diff.quarantined.len(),
+            "orphan_tables_count": diff.orphans.tables.len(),
+            "orphan_results_count": diff.orphans.results.len(),
             "season_distribution": season_stats
         },
         "players": {
@@ -613,6 +445,7 @@ pub async fn dry_run_sync(State(repo): State<LeagueRepository>) -> Response {
             "existing_players": all_players.difference(&new_players).collect::<std::collections::BTreeSet<_>>()
         },
         "warnings": warnings,
+        "diff": diff,
         "games_preview": games_info.iter().take(10).collect::<Vec<_>>(),
         "games_by_season": {
             "season_0": games_info.iter().filter(|g| g["season_num"] == 0).count(),
@@ -621,5 +454,5 @@ pub async fn dry_run_sync(State(repo): State<LeagueRepository>) -> Response {
         }
     });
 
-    (StatusCode::OK, axum::Json(result)).into_response()
+    Ok((StatusCode::OK, axum::Json(result)).into_response())
 }
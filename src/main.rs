@@ -2,11 +2,16 @@ use sqlx::PgPool;
 use crate::db::LeagueRepository;
 use std::env;
 use dotenv::dotenv;
+use crate::config::SYNC_CONFIG;
 
+mod cli;
+mod config;
 mod models;
 mod handlers;
 mod routes;
 mod db;
+mod error;
+mod sync;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -16,6 +21,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // 从环境变量获取数据库URL
     let database_url = env::var("DATABASE_URL").expect("DATABASE_URL必须在环境变量中设置");
 
+    // 加载同步数据源配置，配置错误时启动即失败
+    lazy_static::initialize(&SYNC_CONFIG);
+    for source in &SYNC_CONFIG.sources {
+        println!("同步数据源: {} -> {}", source.name, source.url);
+    }
+    println!("上游缺失数据处理方式: {}", SYNC_CONFIG.reconcile.as_str());
+    for hook in &SYNC_CONFIG.webhooks {
+        let signed = if hook.secret.is_some() { "签名" } else { "未签名" };
+        println!("同步完成通知: {} -> {}（{}）", hook.name, hook.url, signed);
+    }
+
     // 获取应用路由
     let app = routes::create_router();
 
@@ -23,6 +39,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let pool = PgPool::connect(&database_url).await?;
     let league_repo = LeagueRepository::new(pool.clone());
 
+    // 命令行导入: import <路径> [--dry-run]，导入后退出，不启动服务器
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("import") {
+        if let Err(msg) = cli::import(&league_repo, &args[1..]).await {
+            eprintln!("❌ {}", msg);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // 启动后台定时同步
+    sync::scheduler::spawn(league_repo.clone());
+
     // 将仓库实例存储到应用程序状态中
     let app = app.with_state(league_repo);
 
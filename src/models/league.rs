@@ -12,6 +12,12 @@ pub struct GameInfo {
     pub player_results: Vec<PlayerResult>,
     pub season_num: i32,
     pub table_num: i32,
+    // 描述中的分组或阶段，例如 Group A、Finals
+    pub group_name: Option<String>,
+    // 所属的聚会场次（上游 sid）
+    pub session_id: Option<i32>,
+    // 上游的比赛ID
+    pub source_gid: Option<i32>,
 }
 
 // 添加PlayerResult结构体
@@ -26,7 +32,7 @@ pub struct PlayerResult {
     pub total: f64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LeaguePlayer {
     pub id: i32,
     pub name: String,
@@ -43,7 +49,10 @@ impl LeaguePlayer {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LeagueGame {
+    // 有登记时间时为登记时间，否则为比赛日期零点
     pub game_time: Option<NaiveDateTime>,
+    pub played_date: Option<NaiveDate>,
+    pub registered: Option<NaiveDateTime>,
     pub season_num: i32,
     pub table_num: i32,
     pub processed: bool,
@@ -52,11 +61,21 @@ pub struct LeagueGame {
     pub s: i32,
     pub w: i32,
     pub n: i32,
+    pub session_id: Option<i32>,
+    // 上游的比赛ID，唯一
+    pub source_gid: Option<i32>,
+    // 上游的原始描述
+    pub description: Option<String>,
+    // 从描述中解析出的分组或阶段
+    pub group_name: Option<String>,
 }
 
 impl LeagueGame {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         game_time: Option<NaiveDateTime>,
+        played_date: Option<NaiveDate>,
+        registered: Option<NaiveDateTime>,
         season_num: i32,
         table_num: i32,
         processed: bool,
@@ -65,9 +84,15 @@ impl LeagueGame {
         s: i32,
         w: i32,
         n: i32,
+        session_id: Option<i32>,
+        source_gid: Option<i32>,
+        description: Option<String>,
+        group_name: Option<String>,
     ) -> Self {
         Self {
             game_time,
+            played_date,
+            registered,
             season_num,
             table_num,
             processed,
@@ -76,11 +101,42 @@ impl LeagueGame {
             s,
             w,
             n,
+            session_id,
+            source_gid,
+            description,
+            group_name,
         }
     }
 }
 
+// 一次聚会（上游的 session），包含若干比赛桌
 #[derive(Debug, Serialize, Deserialize)]
+pub struct LeagueSession {
+    pub id: i32,
+    pub name: String,
+    pub group_name: String,
+    pub date: Option<NaiveDate>,
+}
+
+// 场次详情，包含该场次的所有比赛
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionInfo {
+    #[serde(flatten)]
+    pub session: LeagueSession,
+    pub games: Vec<GameInfo>,
+}
+
+// 玩家合并结果，被合并的玩家已删除，其名字成为目标玩家的曾用名
+#[derive(Debug, Serialize)]
+pub struct PlayerMerge {
+    pub from: LeaguePlayer,
+    pub into: LeaguePlayer,
+    pub results_moved: u64,
+    pub tables_updated: u64,
+    pub aliases_moved: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LeagueResult {
     pub id: i32,
     pub table_id: i32,
@@ -93,6 +149,7 @@ pub struct LeagueResult {
 }
 
 impl LeagueResult {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         id: i32,
         table_id: i32,
@@ -114,4 +171,102 @@ impl LeagueResult {
             total,
         }
     }
-}
\ No newline at end of file
+}
+// 赛季排行榜中的一行
+#[derive(Debug, Serialize)]
+pub struct StandingRow {
+    // 未达到最少比赛数的玩家没有名次
+    pub rank: Option<usize>,
+    pub eligible: bool,
+    pub player_id: i32,
+    pub player_name: String,
+    pub games: i64,
+    pub total_points: f64,
+    pub average_points: f64,
+    pub average_position: f64,
+    pub first_count: i64,
+    pub second_count: i64,
+    pub third_count: i64,
+    pub fourth_count: i64,
+    pub uma_sum: f64,
+    pub penalty_sum: f64,
+}
+
+// 赛季排行榜及其使用的排名规则
+#[derive(Debug, Serialize)]
+pub struct SeasonStandings {
+    pub season_num: i32,
+    pub group_name: Option<String>,
+    pub rank_by: String,
+    pub min_games: i64,
+    pub tie_breakers: Vec<String>,
+    pub standings: Vec<StandingRow>,
+}
+
+// 赛季全部比赛的一页，total 为排序前的比赛总数
+#[derive(Debug, Serialize)]
+pub struct SeasonMatches {
+    pub season_num: i32,
+    pub sort: String,
+    pub order: String,
+    pub page: usize,
+    pub per_page: usize,
+    pub total: usize,
+    pub games: Vec<GameInfo>,
+}
+
+// 一至四位的次数
+#[derive(Debug, Serialize)]
+pub struct PlacementCounts {
+    pub first: i64,
+    pub second: i64,
+    pub third: i64,
+    pub fourth: i64,
+}
+
+// 汇总中引用的单场比赛
+#[derive(Debug, Serialize)]
+pub struct SummaryGame {
+    pub game_id: i32,
+    pub season_num: i32,
+    pub table_num: i32,
+    pub description: Option<String>,
+    pub played_date: Option<NaiveDate>,
+    pub score: f64,
+    pub position: i32,
+    pub total: f64,
+}
+
+// 同桌玩家，total 为与其同桌时本人的总分合计
+#[derive(Debug, Clone, Serialize)]
+pub struct TablemateSummary {
+    pub player_name: String,
+    pub games: i64,
+    pub total: f64,
+}
+
+// 玩家赛季汇总，没有比赛时平均值和比率为 null
+#[derive(Debug, Serialize)]
+pub struct PlayerSummary {
+    pub player_id: i32,
+    pub player_name: String,
+    // 为空表示所有赛季
+    pub season_num: Option<i32>,
+    pub games: i64,
+    pub score_sum: f64,
+    pub uma_sum: f64,
+    pub penalty_sum: f64,
+    pub total_sum: f64,
+    pub average_score: Option<f64>,
+    pub average_total: Option<f64>,
+    pub average_position: Option<f64>,
+    pub placements: PlacementCounts,
+    // 一位率
+    pub top_rate: Option<f64>,
+    // 避四率，即非四位的比例
+    pub last_avoidance_rate: Option<f64>,
+    pub best_game: Option<SummaryGame>,
+    pub worst_game: Option<SummaryGame>,
+    pub best_tablemate: Option<TablemateSummary>,
+    pub worst_tablemate: Option<TablemateSummary>,
+}
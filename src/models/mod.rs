@@ -1 +1,2 @@
-pub mod league;
\ No newline at end of file
+pub mod league;
+pub mod sync;
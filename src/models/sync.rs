@@ -0,0 +1,89 @@
+use serde::Serialize;
+use chrono::{DateTime, Utc};
+
+// 同步运行记录
+#[derive(Debug, Serialize)]
+pub struct SyncRun {
+    pub id: i32,
+    pub source: String,
+    pub triggered_by: String,
+    pub status: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub players_created: i32,
+    pub players_renamed: i32,
+    pub games_saved: i32,
+    pub games_updated: i32,
+    pub results_written: i32,
+    pub orphan_tables: i32,
+    pub orphan_results: i32,
+    pub games_quarantined: i32,
+    // 本次导入的快照，未拉取或未成功时为空
+    pub snapshot_id: Option<i32>,
+    pub warnings: Vec<String>,
+    pub error: Option<String>,
+    // 失败分类：network、http_status、schema、payload_too_large、config 或 database
+    pub error_kind: Option<String>,
+}
+
+// 数据库中存在、但上游已不存在的比赛桌
+#[derive(Debug, Clone, Serialize)]
+pub struct OrphanTable {
+    pub id: i32,
+    pub source_gid: i32,
+    pub description: Option<String>,
+}
+
+// 上游比赛仍在、但该玩家已不在该场比赛中的成绩
+#[derive(Debug, Clone, Serialize)]
+pub struct OrphanResult {
+    pub id: i32,
+    pub table_id: i32,
+    pub source_gid: i32,
+    pub player_id: i32,
+    pub player_name: String,
+}
+
+// 未通过校验而被隔离的上游比赛
+#[derive(Debug, Serialize)]
+pub struct QuarantinedGame {
+    pub id: i32,
+    pub source_gid: i32,
+    pub description: String,
+    pub reasons: Vec<String>,
+    pub payload: serde_json::Value,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub released_at: Option<DateTime<Utc>>,
+}
+
+// 存档的上游原始数据，不含数据本身
+#[derive(Debug, Serialize)]
+pub struct SyncSnapshot {
+    pub id: i32,
+    pub source: String,
+    pub content_hash: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub size_bytes: i32,
+    pub fetched_at: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub imported_at: Option<DateTime<Utc>>,
+}
+
+// 一次webhook投递记录
+#[derive(Debug, Serialize)]
+pub struct WebhookDelivery {
+    pub id: i32,
+    pub webhook: String,
+    pub url: String,
+    pub event: String,
+    pub run_id: Option<i32>,
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
@@ -1,7 +1,7 @@
-use axum::{Router, routing::get};
-use crate::handlers::{sync_trigger, get_players_by_season, get_player_matches_by_season, get_seasons};
+use axum::{Router, extract::DefaultBodyLimit, routing::{get, post}};
+use crate::handlers::{sync_trigger, get_players_by_season, get_player_matches_by_season, get_seasons, get_sessions, get_session, merge_players, get_season_standings, get_season_matches, get_player_summary};
 use crate::db::LeagueRepository;
-use crate::handlers::sync::dry_run_sync;
+use crate::handlers::sync::{cancel_sync, dry_run_sync, sync_status, list_sync_runs, get_sync_run, get_sync_job, sync_job_events, list_quarantine, release_quarantine, upload_sync, UPLOAD_BODY_LIMIT, list_snapshots, get_snapshot, replay_snapshot, list_webhook_deliveries};
 
 pub fn create_router() -> Router<LeagueRepository> {
     Router::new()
@@ -10,7 +10,26 @@ pub fn create_router() -> Router<LeagueRepository> {
         }))
         .route("/sync", get(sync_trigger))
         .route("/sync/dryrun", get(dry_run_sync))
+        .route("/sync/cancel", post(cancel_sync))
+        .route("/sync/upload", post(upload_sync).layer(DefaultBodyLimit::max(UPLOAD_BODY_LIMIT)))
+        .route("/sync/status", get(sync_status))
+        .route("/sync/runs", get(list_sync_runs))
+        .route("/sync/runs/{id}", get(get_sync_run))
+        .route("/sync/jobs/{id}", get(get_sync_job))
+        .route("/sync/jobs/{id}/events", get(sync_job_events))
+        .route("/sync/snapshots", get(list_snapshots))
+        .route("/sync/snapshots/{id}", get(get_snapshot))
+        .route("/sync/snapshots/{id}/replay", post(replay_snapshot))
+        .route("/sync/webhooks/deliveries", get(list_webhook_deliveries))
+        .route("/sync/quarantine", get(list_quarantine))
+        .route("/sync/quarantine/{gid}/release", post(release_quarantine))
         .route("/seasons", get(get_seasons))
+        .route("/seasons/{n}/standings", get(get_season_standings))
+        .route("/season/{n}/matches", get(get_season_matches))
         .route("/players", get(get_players_by_season))
+        .route("/players/{id}/merge", post(merge_players))
         .route("/player/{name}/matches", get(get_player_matches_by_season))
+        .route("/player/{name}/summary", get(get_player_summary))
+        .route("/sessions", get(get_sessions))
+        .route("/sessions/{sid}", get(get_session))
 }
\ No newline at end of file
@@ -0,0 +1,454 @@
+use crate::models::league::{GameInfo, LeagueGame, LeaguePlayer, LeagueResult, PlayerResult};
+use crate::sync::store::SyncStore;
+use crate::sync::{description, DataGame, DataRoot, SyncSummary};
+use chrono::NaiveDate;
+use sqlx::Error;
+use std::collections::HashMap;
+
+// 玩家和比赛的写入逻辑，只通过 SyncStore 读写，不依赖具体的数据库
+
+// 同步过程中使用的玩家映射
+pub struct PlayerMaps {
+    // JSON pid -> 玩家名
+    pub pid_name: HashMap<usize, String>,
+    // 数据库ID -> 玩家名
+    pub existing_id_name: HashMap<i32, String>,
+    // 玩家名 -> 数据库ID
+    pub name_id: HashMap<String, i32>,
+}
+
+// 第一步：检查缺失的ID和不一致的姓名
+pub async fn sync_players<S: SyncStore>(
+    store: &mut S,
+    data: &DataRoot,
+    summary: &mut SyncSummary,
+) -> Result<PlayerMaps, Error> {
+    // 构建pid到玩家名映射
+    let mut pid_name_map = HashMap::new();
+    for p in &data.collection.players {
+        pid_name_map.insert(p.pid, p.name.clone());
+    }
+
+    // 获取现有玩家
+    let existing_players = store.list_players().await?;
+    // 已合并到其他玩家的pid，成绩归入合并目标，不再重新创建
+    let merged = store.merged_player_ids().await?;
+
+    println!("开始检查玩家数据一致性...");
+
+    // 构建现有玩家的ID到姓名映射
+    let mut existing_id_name_map = HashMap::new();
+    for player in &existing_players {
+        existing_id_name_map.insert(player.id, player.name.clone());
+    }
+
+    // 检查JSON中的每个玩家
+    for json_player in &data.collection.players {
+        let pid = json_player.pid as i32;
+        let json_name = &json_player.name;
+
+        if let Some(target) = merged.get(&pid) {
+            println!("↪ ID {} 的玩家 '{}' 已合并到 ID {}", pid, json_name, target);
+            continue;
+        }
+
+        if let Some(existing_name) = existing_id_name_map.get(&pid) {
+            // ID存在，检查姓名是否一致
+            if existing_name != json_name {
+                println!("⚠️  发现ID {} 的姓名不一致：", pid);
+                println!("   数据库中: '{}'", existing_name);
+                println!("   JSON中:   '{}'", json_name);
+                println!("   正在更新...");
+
+                let update_player = LeaguePlayer::new(pid, json_name.clone());
+                store.update_player(&update_player).await?;
+                println!("✅ 成功更新ID {} 的玩家姓名: '{}' -> '{}'", pid, existing_name, json_name);
+                // 更新本地映射
+                existing_id_name_map.insert(pid, json_name.clone());
+                summary.updated_players_count += 1;
+            } else {
+                println!("✓ ID {} 的玩家 '{}' 信息一致", pid, json_name);
+            }
+        } else {
+            // ID不存在，需要创建新玩家
+            println!("📝 发现缺失的玩家ID {}，姓名: {}，正在创建...", pid, json_name);
+            let new_player = LeaguePlayer::new(pid, json_name.clone());
+            let created_id = store.create_player_with_id(&new_player).await?;
+            println!("✅ 成功创建玩家: {} (ID: {})", json_name, created_id);
+            existing_id_name_map.insert(pid, json_name.clone());
+            summary.created_players_count += 1;
+        }
+    }
+
+    println!("玩家数据一致性检查完成:");
+    println!("  - 更新了 {} 个玩家的姓名", summary.updated_players_count);
+    println!("  - 创建了 {} 个新玩家", summary.created_players_count);
+    println!("  - 总共检查了 {} 个玩家", data.collection.players.len());
+
+    // 构建姓名到ID的映射，用于后续的游戏处理
+    let mut player_id_map = HashMap::new();
+    for (id, name) in &existing_id_name_map {
+        player_id_map.insert(name.clone(), *id);
+    }
+    for json_player in &data.collection.players {
+        if let Some(&target) = merged.get(&(json_player.pid as i32)) {
+            player_id_map.insert(json_player.name.clone(), target);
+        }
+    }
+
+    Ok(PlayerMaps {
+        pid_name: pid_name_map,
+        existing_id_name: existing_id_name_map,
+        name_id: player_id_map,
+    })
+}
+
+// 创建/更新单场比赛及其成绩
+pub async fn sync_game<S: SyncStore>(
+    store: &mut S,
+    data: &DataRoot,
+    game: &DataGame,
+    session_id: Option<i32>,
+    maps: &mut PlayerMaps,
+    summary: &mut SyncSummary,
+) -> Result<(), Error> {
+    // 生成PlayerResult列表
+    let mut player_results = Vec::new();
+    for result in &game.results {
+        let player_name = maps.pid_name.get(&result.player).cloned().unwrap_or_else(|| {
+            summary.warnings.push(format!("游戏 {} 中找不到玩家 ID {}", game.gid, result.player));
+            "Unknown".to_string()
+        });
+        let seat = result.seat.clone();
+        let score = result.result;
+        let position = result.position.unwrap_or(0) as i32;
+        let uma = result.uma.unwrap_or(0.0);
+        let penalty = result.penalty.unwrap_or(0.0);
+        let total = result.total.unwrap_or(0.0);
+        player_results.push(PlayerResult {
+            seat,
+            player_name,
+            score,
+            position,
+            uma,
+            penalty,
+            total,
+        });
+    }
+
+    // 从描述中解析赛季、分组和桌号，无法解析时记录警告并用gid作为桌号
+    let (parsed, warning) = description::parse_or_fallback(&game.description, game.gid);
+    summary.warnings.extend(warning);
+
+    let played_date = NaiveDate::parse_from_str(&game.played, "%Y-%m-%d").ok();
+    if played_date.is_none() {
+        summary.warnings.push(format!("游戏 {} 的比赛日期无法解析: '{}'", game.gid, game.played));
+    }
+
+    // 创建GameInfo对象
+    let game_info = GameInfo {
+        game_id: game.gid as i32,
+        played_date: played_date.unwrap_or_default(),
+        registered: game.registered,
+        description: game.description.clone(),
+        processed: true,
+        player_results,
+        season_num: parsed.season_num,
+        table_num: parsed.table_num,
+        group_name: parsed.group,
+        session_id,
+        source_gid: Some(game.gid as i32),
+    };
+
+    // 步骤1：为尚未映射的玩家分配数据库ID
+    for player_result in &game_info.player_results {
+        let player_name = &player_result.player_name;
+        if maps.name_id.contains_key(player_name) {
+            continue;
+        }
+
+        // 查找该玩家在JSON数据中的pid
+        let player_pid = data.collection.players.iter()
+            .find(|p| p.name == *player_name)
+            .map(|p| p.pid as i32);
+
+        let player_id = match player_pid {
+            Some(pid) if maps.existing_id_name.contains_key(&pid) => {
+                // ID已存在，更新该ID对应的玩家名字
+                println!("ID {} 已存在，更新该ID对应的玩家名字为: {}", pid, player_name);
+                store.update_player(&LeaguePlayer::new(pid, player_name.clone())).await?;
+                pid
+            }
+            Some(pid) => {
+                // 使用JSON中的pid作为数据库ID创建玩家
+                let created_id = store.create_player_with_id(&LeaguePlayer::new(pid, player_name.clone())).await?;
+                println!("创建新玩家: {} (ID: {}, 来自JSON pid: {})", player_name, created_id, pid);
+                created_id
+            }
+            None => {
+                println!("警告: 玩家 {} 在JSON数据中找不到对应的pid，使用自动分配ID", player_name);
+                let new_player_id = store.create_player(&LeaguePlayer::new(-1, player_name.clone())).await?;
+                println!("创建新玩家 {} 成功 (自动分配ID: {})", player_name, new_player_id);
+                new_player_id
+            }
+        };
+        maps.existing_id_name.insert(player_id, player_name.clone());
+        maps.name_id.insert(player_name.clone(), player_id);
+    }
+
+    // 步骤2：创建/更新游戏记录，使用已获取的玩家ID
+    let mut e_id = 0;
+    let mut s_id = 0;
+    let mut w_id = 0;
+    let mut n_id = 0;
+    for player_result in &game_info.player_results {
+        if let Some(&player_id) = maps.name_id.get(&player_result.player_name) {
+            // 统一seat匹配，去除括号并大写
+            let seat = player_result.seat.trim_matches(|c| c == '[' || c == ']').to_uppercase();
+            match seat.as_str() {
+                "E" | "EAST" => e_id = player_id,
+                "S" | "SOUTH" => s_id = player_id,
+                "W" | "WEST" => w_id = player_id,
+                "N" | "NORTH" => n_id = player_id,
+                _ => {}
+            }
+        }
+    }
+
+    // game_time 优先使用登记时间，没有时使用比赛日期零点
+    let game_time = game_info
+        .registered
+        .or_else(|| played_date.and_then(|d| d.and_hms_opt(0, 0, 0)));
+    let mut game_db = LeagueGame::new(
+        game_time,
+        played_date,
+        game_info.registered,
+        game_info.season_num,
+        game_info.table_num,
+        game_info.processed,
+        game_info.game_id,
+        e_id,
+        s_id,
+        w_id,
+        n_id,
+        game_info.session_id,
+        game_info.source_gid,
+        Some(game_info.description.clone()),
+        game_info.group_name.clone(),
+    );
+
+    // 优先按上游gid匹配，找不到时再按赛季和桌号匹配尚未记录gid的旧记录
+    let existing_game = match store.get_game_by_source_gid(game.gid as i32).await? {
+        Some(existing_game) => Some(existing_game),
+        None => store.get_legacy_game_by_season_and_table(game_info.season_num, game_info.table_num).await?,
+    };
+    match existing_game {
+        Some(existing_game) => {
+            println!("发现gid {} 对应的游戏记录（赛季 {}，桌号 {}），ID: {}，将进行更新",
+                     game.gid, game_info.season_num, game_info.table_num, existing_game.id);
+            game_db.id = existing_game.id;
+            // data.json 不含登记时间，保留之前从比赛页面补充的值
+            if game_db.registered.is_none() && existing_game.registered.is_some() {
+                game_db.registered = existing_game.registered;
+                game_db.game_time = existing_game.registered;
+            }
+            // 比赛页面不含场次信息，同样保留原有场次
+            if game_db.session_id.is_none() {
+                game_db.session_id = existing_game.session_id;
+            }
+            store.update_game(&game_db).await?;
+            summary.updated_games_count += 1;
+        },
+        None => {
+            let new_game_id = store.create_game(&game_db).await?;
+            println!("游戏保存成功: ID {}", new_game_id);
+            summary.saved_count += 1;
+            game_db.id = new_game_id;
+        }
+    }
+
+    // 步骤3：创建/更新玩家成绩
+    for result in &game_info.player_results {
+        let player_id = {
+            let seat = result.seat.trim_matches(|c| c == '[' || c == ']').to_uppercase();
+            match seat.as_str() {
+                "E" | "EAST" => game_db.e,
+                "S" | "SOUTH" => game_db.s,
+                "W" | "WEST" => game_db.w,
+                "N" | "NORTH" => game_db.n,
+                _ => {
+                    summary.warnings.push(format!(
+                        "游戏 {} 中玩家 {} 的座位无法识别: {}，成绩未写入",
+                        game.gid, result.player_name, result.seat
+                    ));
+                    continue;
+                }
+            }
+        };
+
+        match store.get_result_by_table_and_player(game_db.id, player_id).await? {
+            Some(mut existing_result) => {
+                existing_result.result = result.score;
+                existing_result.position = result.position;
+                existing_result.uma = result.uma;
+                existing_result.penalty = result.penalty;
+                existing_result.total = result.total;
+                store.update_result(&existing_result).await?;
+                summary.results_written_count += 1;
+            },
+            None => {
+                let game_result = LeagueResult::new(
+                    0,
+                    game_db.id,
+                    player_id,
+                    result.score,
+                    result.position,
+                    result.uma,
+                    result.penalty,
+                    result.total
+                );
+                store.create_result(&game_result).await?;
+                summary.results_written_count += 1;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::store::MemoryStore;
+    use serde_json::json;
+
+    // 四名玩家的一场比赛，东南西北依次为 pid 1-4
+    fn fixture(players: &[(usize, &str)], gid: usize, description: &str, scores: [f64; 4]) -> DataRoot {
+        let players: Vec<_> = players.iter().map(|(pid, name)| json!({ "pid": pid, "name": name })).collect();
+        let results: Vec<_> = ["E", "S", "W", "N"]
+            .iter()
+            .zip(scores)
+            .enumerate()
+            .map(|(i, (seat, score))| json!({ "player": i + 1, "result": score, "seat": seat, "position": i + 1 }))
+            .collect();
+        let data = json!({
+            "collection": {
+                "players": players,
+                "games": [{
+                    "gid": gid,
+                    "played": "2025-03-01",
+                    "description": description,
+                    "players": [1, 2, 3, 4],
+                    "results": results,
+                }],
+                "sessions": [],
+            }
+        });
+        serde_json::from_value(data).unwrap()
+    }
+
+    const PLAYERS: [(usize, &str); 4] = [(1, "Alice"), (2, "Bob"), (3, "Carol"), (4, "Dave")];
+    const SCORES: [f64; 4] = [40000.0, 30000.0, 20000.0, 10000.0];
+
+    async fn sync_all(store: &mut MemoryStore, data: &DataRoot, summary: &mut SyncSummary) {
+        let mut maps = sync_players(store, data, summary).await.unwrap();
+        for game in &data.collection.games {
+            sync_game(store, data, game, None, &mut maps, summary).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn creates_new_players_with_upstream_ids() {
+        let data = fixture(&PLAYERS, 10, "Season 1: Table 2", SCORES);
+        let mut store = MemoryStore::default();
+        let mut summary = SyncSummary::default();
+
+        let maps = sync_players(&mut store, &data, &mut summary).await.unwrap();
+
+        assert_eq!(summary.created_players_count, 4);
+        assert_eq!(store.player_name(3), Some("Carol"));
+        assert_eq!(maps.name_id.get("Dave"), Some(&4));
+    }
+
+    #[tokio::test]
+    async fn renames_existing_player() {
+        let data = fixture(&PLAYERS, 10, "Season 1: Table 2", SCORES);
+        let mut store = MemoryStore {
+            players: vec![LeaguePlayer::new(1, "Alicia".to_string())],
+            ..Default::default()
+        };
+        let mut summary = SyncSummary::default();
+
+        let maps = sync_players(&mut store, &data, &mut summary).await.unwrap();
+
+        assert_eq!(summary.updated_players_count, 1);
+        assert_eq!(summary.created_players_count, 3);
+        assert_eq!(store.player_name(1), Some("Alice"));
+        assert_eq!(maps.name_id.get("Alice"), Some(&1));
+        assert!(!maps.name_id.contains_key("Alicia"));
+    }
+
+    #[tokio::test]
+    async fn writes_merged_player_results_to_target() {
+        let data = fixture(&PLAYERS, 10, "Season 1: Table 2", SCORES);
+        let mut store = MemoryStore {
+            players: vec![LeaguePlayer::new(9, "Alice Main".to_string())],
+            merged: HashMap::from([(1, 9)]),
+            ..Default::default()
+        };
+        let mut summary = SyncSummary::default();
+
+        sync_all(&mut store, &data, &mut summary).await;
+
+        // 已合并的 pid 1 不重新创建，座位和成绩归入合并目标
+        assert_eq!(store.player_name(1), None);
+        assert_eq!(store.games[0].e, 9);
+        let result = store.results.iter().find(|r| r.player_id == 9).unwrap();
+        assert_eq!(result.result, 40000.0);
+        assert!(store.results.iter().all(|r| r.player_id != 1));
+    }
+
+    #[tokio::test]
+    async fn updates_existing_legacy_table() {
+        let data = fixture(&PLAYERS, 10, "Season 1: Group A: Table 2", SCORES);
+        let mut legacy = LeagueGame::new(None, None, None, 1, 2, true, 0, 1, 2, 3, 4, Some(7), None, None, None);
+        legacy.id = 5;
+        let mut store = MemoryStore { games: vec![legacy], ..Default::default() };
+        let mut summary = SyncSummary::default();
+
+        sync_all(&mut store, &data, &mut summary).await;
+
+        assert_eq!(summary.saved_count, 0);
+        assert_eq!(summary.updated_games_count, 1);
+        assert_eq!(store.games.len(), 1);
+        let game = &store.games[0];
+        assert_eq!(game.id, 5);
+        assert_eq!(game.source_gid, Some(10));
+        assert_eq!(game.group_name.as_deref(), Some("Group A"));
+        // 数据源不含场次时保留原有场次
+        assert_eq!(game.session_id, Some(7));
+        assert!(store.results.iter().all(|r| r.table_id == 5));
+    }
+
+    #[tokio::test]
+    async fn upserts_results_on_resync() {
+        let mut store = MemoryStore::default();
+        let mut summary = SyncSummary::default();
+        let data = fixture(&PLAYERS, 10, "Season 1: Table 2", SCORES);
+        sync_all(&mut store, &data, &mut summary).await;
+        assert_eq!(summary.saved_count, 1);
+        assert_eq!(store.results.len(), 4);
+
+        let corrected = fixture(&PLAYERS, 10, "Season 1: Table 2", [35000.0, 35000.0, 20000.0, 10000.0]);
+        let mut summary = SyncSummary::default();
+        sync_all(&mut store, &corrected, &mut summary).await;
+
+        assert_eq!(summary.saved_count, 0);
+        assert_eq!(summary.updated_games_count, 1);
+        assert_eq!(summary.results_written_count, 4);
+        assert_eq!(store.games.len(), 1);
+        assert_eq!(store.results.len(), 4);
+        let alice = store.results.iter().find(|r| r.player_id == 1).unwrap();
+        assert_eq!(alice.result, 35000.0);
+    }
+}
@@ -0,0 +1,135 @@
+use regex::Regex;
+use serde::Serialize;
+
+lazy_static::lazy_static! {
+    static ref SEASON_PART_RE: Regex = Regex::new(r"(?i)^Season\s+(\d+)$").unwrap();
+    static ref TABLE_PART_RE: Regex = Regex::new(r"(?i)^Table\s+(\d+)$").unwrap();
+    // 严格解析失败时兜底：描述中任意位置的 Season X: ...: Table Y（前后可有其他文字），
+    // 例如 "Playoffs Season 2: Table 5"、"Season 2: Table 5 (replay)"
+    static ref LOOSE_RE: Regex = Regex::new(r"(?i)Season (\d+)(?:: [^:]+)*: Table (\d+)").unwrap();
+    // 连桌号也找不到时，描述中任意位置的 Season X
+    static ref SEASON_RE: Regex = Regex::new(r"(?i)Season\s+(\d+)").unwrap();
+}
+
+// 从比赛描述中解析出的赛季、分组和桌号
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GameDescription {
+    pub season_num: i32,
+    // 赛季和桌号之间的部分，例如 "Group A"、"Finals"，没有时为 None
+    pub group: Option<String>,
+    pub table_num: i32,
+}
+
+// 描述格式：Season X: Table Y，或中间带分组/阶段的 Season X: Group A: Table Y
+// 分组可以有多段，例如 "Season 2: Finals: Group B: Table 1" 的分组为 "Finals: Group B"
+pub fn parse(desc: &str) -> Result<GameDescription, String> {
+    let parts: Vec<&str> = desc.split(':').map(str::trim).collect();
+    let (Some(first), Some(last)) = (parts.first(), parts.last()) else {
+        return Err("描述为空".to_string());
+    };
+    if parts.len() < 2 {
+        return Err("应为 Season X: Table Y 格式".to_string());
+    }
+    let season_num = SEASON_PART_RE
+        .captures(first)
+        .and_then(|caps| caps[1].parse::<i32>().ok())
+        .ok_or("开头不是 Season X")?;
+    let table_num = TABLE_PART_RE
+        .captures(last)
+        .and_then(|caps| caps[1].parse::<i32>().ok())
+        .ok_or("结尾不是 Table Y")?;
+
+    let middle = &parts[1..parts.len() - 1];
+    if middle.iter().any(|p| p.is_empty()) {
+        return Err("分组部分为空".to_string());
+    }
+    let group = (!middle.is_empty()).then(|| middle.join(": "));
+    Ok(GameDescription { season_num, group, table_num })
+}
+
+// 严格解析失败时先按宽松格式取赛季和桌号（不记录分组）；仍失败时赛季取描述中的 Season X（没有时为0），
+// 桌号用gid。两种情况都返回说明原因的警告
+pub fn parse_or_fallback(desc: &str, gid: usize) -> (GameDescription, Option<String>) {
+    match parse(desc) {
+        Ok(parsed) => (parsed, None),
+        Err(reason) => {
+            if let Some(caps) = LOOSE_RE.captures(desc)
+                && let (Ok(season_num), Ok(table_num)) = (caps[1].parse::<i32>(), caps[2].parse::<i32>())
+            {
+                let warning = format!(
+                    "游戏 {} 的描述 '{}' 格式不规范（{}），按赛季 {}、桌号 {} 处理，不记录分组",
+                    gid, desc, reason, season_num, table_num
+                );
+                return (GameDescription { season_num, group: None, table_num }, Some(warning));
+            }
+            let season_num = SEASON_RE
+                .captures(desc)
+                .and_then(|caps| caps[1].parse::<i32>().ok())
+                .unwrap_or(0);
+            let warning = format!(
+                "游戏 {} 的描述 '{}' 无法解析（{}），按赛季 {}、桌号 {}（gid）处理",
+                gid, desc, reason, season_num, gid
+            );
+            (GameDescription { season_num, group: None, table_num: gid as i32 }, Some(warning))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(season_num: i32, group: Option<&str>, table_num: i32) -> GameDescription {
+        GameDescription { season_num, group: group.map(str::to_string), table_num }
+    }
+
+    #[test]
+    fn parse_season_and_table() {
+        assert_eq!(parse("Season 2: Table 5"), Ok(parsed(2, None, 5)));
+        assert_eq!(parse("season 2 : table 5"), Ok(parsed(2, None, 5)));
+    }
+
+    #[test]
+    fn parse_group() {
+        assert_eq!(parse("Season 1: Group A: Table 3"), Ok(parsed(1, Some("Group A"), 3)));
+        assert_eq!(
+            parse("Season 2: Finals: Group B: Table 1"),
+            Ok(parsed(2, Some("Finals: Group B"), 1))
+        );
+    }
+
+    #[test]
+    fn parse_rejects_extra_text() {
+        assert!(parse("Playoffs Season 2: Table 5").is_err());
+        assert!(parse("Season 2: Table 5 (replay)").is_err());
+        assert!(parse("Season 2: : Table 5").is_err());
+        assert!(parse("Season 2").is_err());
+        assert!(parse("").is_err());
+    }
+
+    #[test]
+    fn fallback_keeps_table_from_loose_description() {
+        for desc in ["Playoffs Season 2: Table 5", "Season 2: Table 5 (replay)", "Final Season 2: Group A: Table 5!"] {
+            let (game, warning) = parse_or_fallback(desc, 99);
+            assert_eq!(game, parsed(2, None, 5), "{}", desc);
+            assert!(warning.is_some());
+        }
+    }
+
+    #[test]
+    fn fallback_uses_gid_without_table() {
+        let (game, warning) = parse_or_fallback("Season 3 friendly", 42);
+        assert_eq!(game, parsed(3, None, 42));
+        assert!(warning.is_some());
+
+        let (game, _) = parse_or_fallback("friendly", 42);
+        assert_eq!(game, parsed(0, None, 42));
+    }
+
+    #[test]
+    fn fallback_not_used_for_valid_description() {
+        let (game, warning) = parse_or_fallback("Season 1: Group A: Table 3", 42);
+        assert_eq!(game, parsed(1, Some("Group A"), 3));
+        assert!(warning.is_none());
+    }
+}
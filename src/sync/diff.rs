@@ -0,0 +1,363 @@
+use crate::config::ReconcileMode;
+use crate::db::LeagueTransaction;
+use crate::models::league::{LeagueGame, LeagueResult};
+use crate::sync::reconcile::{self, Orphans};
+use crate::sync::{description, validate, DataResult, DataRoot};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+// 浮点成绩比较的容差
+const SCORE_EPSILON: f64 = 1e-9;
+
+// 一条成绩的可比较字段
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ResultValues {
+    pub score: f64,
+    pub position: i32,
+    pub uma: f64,
+    pub penalty: f64,
+    pub total: f64,
+}
+
+impl ResultValues {
+    fn from_data(result: &DataResult) -> Self {
+        ResultValues {
+            score: result.result,
+            position: result.position.unwrap_or(0) as i32,
+            uma: result.uma.unwrap_or(0.0),
+            penalty: result.penalty.unwrap_or(0.0),
+            total: result.total.unwrap_or(0.0),
+        }
+    }
+
+    // 与旧值相比发生变化的字段
+    fn changed_fields(&self, old: &ResultValues) -> Vec<&'static str> {
+        let mut fields = Vec::new();
+        if (self.score - old.score).abs() > SCORE_EPSILON {
+            fields.push("score");
+        }
+        if self.position != old.position {
+            fields.push("position");
+        }
+        if (self.uma - old.uma).abs() > SCORE_EPSILON {
+            fields.push("uma");
+        }
+        if (self.penalty - old.penalty).abs() > SCORE_EPSILON {
+            fields.push("penalty");
+        }
+        if (self.total - old.total).abs() > SCORE_EPSILON {
+            fields.push("total");
+        }
+        fields
+    }
+
+    fn field(&self, name: &str) -> String {
+        match name {
+            "score" => self.score.to_string(),
+            "position" => self.position.to_string(),
+            "uma" => self.uma.to_string(),
+            "penalty" => self.penalty.to_string(),
+            _ => self.total.to_string(),
+        }
+    }
+}
+
+// 将要新建的比赛桌
+#[derive(Debug, Serialize)]
+pub struct TableCreate {
+    pub gid: usize,
+    pub description: String,
+    pub season_num: i32,
+    pub group_name: Option<String>,
+    pub table_num: i32,
+    pub players: Vec<String>,
+}
+
+// 已有比赛桌上将要新增或修改的成绩，old 为空表示新增
+#[derive(Debug, Serialize)]
+pub struct ResultChange {
+    pub gid: usize,
+    pub table_id: i32,
+    pub player_id: i32,
+    pub player_name: String,
+    pub old: Option<ResultValues>,
+    pub new: ResultValues,
+    pub changed_fields: Vec<&'static str>,
+}
+
+// 未通过校验、同步时将被隔离的比赛
+#[derive(Debug, Serialize)]
+pub struct InvalidGame {
+    pub gid: usize,
+    pub description: String,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlayerRename {
+    pub id: i32,
+    pub old_name: String,
+    pub new_name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlayerCreate {
+    pub id: i32,
+    pub name: String,
+}
+
+// 同步预览的差异报告
+#[derive(Debug, Serialize)]
+pub struct SyncDiff {
+    pub tables_created: Vec<TableCreate>,
+    pub results_changed: Vec<ResultChange>,
+    pub players_renamed: Vec<PlayerRename>,
+    pub players_created: Vec<PlayerCreate>,
+    pub quarantined: Vec<InvalidGame>,
+    pub reconcile: &'static str,
+    pub orphans: Orphans,
+}
+
+// 对比上游数据与数据库现状，只读
+// 同步时玩家以上游 pid 作为数据库ID，这里按同样的规则对应
+pub async fn build_diff(tx: &mut LeagueTransaction, data: &DataRoot, reconcile_mode: ReconcileMode) -> Result<SyncDiff, sqlx::Error> {
+    let existing: HashMap<i32, String> = tx
+        .list_players()
+        .await?
+        .into_iter()
+        .map(|p| (p.id, p.name))
+        .collect();
+    let merged = tx.merged_player_ids().await?;
+    let player_id = |pid: usize| merged.get(&(pid as i32)).copied().unwrap_or(pid as i32);
+
+    let mut players_renamed = Vec::new();
+    let mut players_created = Vec::new();
+    for player in data.collection.players.iter().filter(|p| !merged.contains_key(&(p.pid as i32))) {
+        let id = player.pid as i32;
+        match existing.get(&id) {
+            Some(old_name) if *old_name != player.name => players_renamed.push(PlayerRename {
+                id,
+                old_name: old_name.clone(),
+                new_name: player.name.clone(),
+            }),
+            Some(_) => {}
+            None => players_created.push(PlayerCreate { id, name: player.name.clone() }),
+        }
+    }
+
+    let pid_name: HashMap<usize, &str> = data
+        .collection
+        .players
+        .iter()
+        .map(|p| (p.pid, p.name.as_str()))
+        .collect();
+    let player_name = |pid: usize| pid_name.get(&pid).map(|n| n.to_string()).unwrap_or_else(|| format!("Unknown_{}", pid));
+
+    // 未通过校验的比赛不会写入，不计入新建和变更
+    let released = tx.released_quarantine_gids().await?;
+    let (valid, invalid) = validate::partition(&data.collection.games, &released);
+    let quarantined: Vec<InvalidGame> = invalid
+        .into_iter()
+        .map(|(game, reasons)| InvalidGame {
+            gid: game.gid,
+            description: game.description.clone(),
+            reasons,
+        })
+        .collect();
+    let quarantined_gids: HashSet<usize> = quarantined.iter().map(|g| g.gid).collect();
+
+    // 比赛桌和成绩一次批量取回，查询次数与比赛数无关
+    let games: Vec<_> = valid
+        .into_iter()
+        .map(|game| (game, description::parse_or_fallback(&game.description, game.gid).0))
+        .collect();
+    let gids: Vec<i32> = games.iter().map(|(game, _)| game.gid as i32).collect();
+    let by_gid: HashMap<i32, LeagueGame> = tx
+        .get_games_by_source_gids(&gids)
+        .await?
+        .into_iter()
+        .filter_map(|t| t.source_gid.map(|gid| (gid, t)))
+        .collect();
+    let (season_nums, table_nums): (Vec<i32>, Vec<i32>) = games
+        .iter()
+        .filter(|(game, _)| !by_gid.contains_key(&(game.gid as i32)))
+        .map(|(_, parsed)| (parsed.season_num, parsed.table_num))
+        .unzip();
+    let mut legacy: HashMap<(i32, i32), LeagueGame> = HashMap::new();
+    if !season_nums.is_empty() {
+        for t in tx.get_legacy_games(&season_nums, &table_nums).await? {
+            legacy.entry((t.season_num, t.table_num)).or_insert(t);
+        }
+    }
+    let table_ids: Vec<i32> = by_gid.values().chain(legacy.values()).map(|t| t.id).collect();
+    let mut existing_results: HashMap<(i32, i32), LeagueResult> = HashMap::new();
+    for r in tx.get_results_by_tables(&table_ids).await? {
+        existing_results.entry((r.table_id, r.player_id)).or_insert(r);
+    }
+
+    let mut tables_created = Vec::new();
+    let mut results_changed = Vec::new();
+    for (game, parsed) in games {
+        let (season_num, table_num) = (parsed.season_num, parsed.table_num);
+        let table = by_gid
+            .get(&(game.gid as i32))
+            .or_else(|| legacy.get(&(season_num, table_num)));
+        let Some(table) = table else {
+            tables_created.push(TableCreate {
+                gid: game.gid,
+                description: game.description.clone(),
+                season_num,
+                group_name: parsed.group,
+                table_num,
+                players: game.results.iter().map(|r| player_name(r.player)).collect(),
+            });
+            continue;
+        };
+
+        for result in &game.results {
+            let player_id = player_id(result.player);
+            let new = ResultValues::from_data(result);
+            let old = existing_results.get(&(table.id, player_id)).map(|r| ResultValues {
+                score: r.result,
+                position: r.position,
+                uma: r.uma,
+                penalty: r.penalty,
+                total: r.total,
+            });
+            let changed_fields = match &old {
+                Some(old) => new.changed_fields(old),
+                None => vec!["score", "position", "uma", "penalty", "total"],
+            };
+            if changed_fields.is_empty() {
+                continue;
+            }
+            results_changed.push(ResultChange {
+                gid: game.gid,
+                table_id: table.id,
+                player_id,
+                player_name: player_name(result.player),
+                old,
+                new,
+                changed_fields,
+            });
+        }
+    }
+
+    let name_id: HashMap<String, i32> = data
+        .collection
+        .players
+        .iter()
+        .map(|p| (p.name.clone(), player_id(p.pid)))
+        .collect();
+    let orphans = reconcile::find_orphans(tx, data, &name_id, &quarantined_gids).await?;
+
+    Ok(SyncDiff {
+        tables_created,
+        results_changed,
+        players_renamed,
+        players_created,
+        quarantined,
+        reconcile: reconcile_mode.as_str(),
+        orphans,
+    })
+}
+
+impl SyncDiff {
+    pub fn is_empty(&self) -> bool {
+        self.tables_created.is_empty()
+            && self.results_changed.is_empty()
+            && self.players_renamed.is_empty()
+            && self.players_created.is_empty()
+            && self.quarantined.is_empty()
+            && self.orphans.tables.is_empty()
+            && self.orphans.results.is_empty()
+    }
+
+    // 可读的文本报告，+ 新增，~ 修改，! 隔离，- 上游已不存在
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        if self.is_empty() {
+            out.push_str("没有需要同步的变更\n");
+            return out;
+        }
+
+        if !self.players_created.is_empty() {
+            let _ = writeln!(out, "新玩家 {} 名:", self.players_created.len());
+            for p in &self.players_created {
+                let _ = writeln!(out, "  + ID {}: {}", p.id, p.name);
+            }
+        }
+        if !self.players_renamed.is_empty() {
+            let _ = writeln!(out, "玩家改名 {} 名:", self.players_renamed.len());
+            for p in &self.players_renamed {
+                let _ = writeln!(out, "  ~ ID {}: '{}' -> '{}'", p.id, p.old_name, p.new_name);
+            }
+        }
+        if !self.tables_created.is_empty() {
+            let _ = writeln!(out, "新建比赛桌 {} 张:", self.tables_created.len());
+            for t in &self.tables_created {
+                let _ = writeln!(
+                    out,
+                    "  + gid {} {} (赛季 {}，{}桌号 {}): {}",
+                    t.gid,
+                    t.description,
+                    t.season_num,
+                    t.group_name.as_ref().map(|g| format!("分组 {}，", g)).unwrap_or_default(),
+                    t.table_num,
+                    t.players.join(", ")
+                );
+            }
+        }
+        if !self.results_changed.is_empty() {
+            let _ = writeln!(out, "成绩变更 {} 条:", self.results_changed.len());
+            for r in &self.results_changed {
+                match &r.old {
+                    Some(old) => {
+                        let fields: Vec<String> = r
+                            .changed_fields
+                            .iter()
+                            .map(|f| format!("{} {} -> {}", f, old.field(f), r.new.field(f)))
+                            .collect();
+                        let _ = writeln!(out, "  ~ gid {} {}: {}", r.gid, r.player_name, fields.join(", "));
+                    }
+                    None => {
+                        let _ = writeln!(
+                            out,
+                            "  + gid {} {}: score {}, position {}, uma {}, penalty {}, total {}",
+                            r.gid, r.player_name, r.new.score, r.new.position, r.new.uma, r.new.penalty, r.new.total
+                        );
+                    }
+                }
+            }
+        }
+        if !self.quarantined.is_empty() {
+            let _ = writeln!(out, "未通过校验、将被隔离的比赛 {} 场:", self.quarantined.len());
+            for g in &self.quarantined {
+                let _ = writeln!(out, "  ! gid {} {}: {}", g.gid, g.description, g.reasons.join("；"));
+            }
+        }
+        if !self.orphans.tables.is_empty() || !self.orphans.results.is_empty() {
+            let _ = writeln!(
+                out,
+                "上游已不存在 {} 张比赛桌、{} 条成绩（处理方式: {}）:",
+                self.orphans.tables.len(),
+                self.orphans.results.len(),
+                self.reconcile
+            );
+            for t in &self.orphans.tables {
+                let _ = writeln!(
+                    out,
+                    "  - gid {} {} (比赛桌ID {})",
+                    t.source_gid,
+                    t.description.as_deref().unwrap_or(""),
+                    t.id
+                );
+            }
+            for r in &self.orphans.results {
+                let _ = writeln!(out, "  - gid {} {} (成绩ID {})", r.source_gid, r.player_name, r.id);
+            }
+        }
+        out
+    }
+}
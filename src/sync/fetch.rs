@@ -0,0 +1,196 @@
+use crate::config::SYNC_CONFIG;
+use rand::Rng;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::fmt;
+use std::time::Duration;
+
+// 拉取失败的分类，记录在运行记录和同步状态中
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FetchErrorKind {
+    // 连接失败、超时或读取中断
+    Network,
+    // 数据源返回非成功的状态码
+    HttpStatus,
+    // 内容不是预期的 data.json 或比赛页面格式
+    Schema,
+    // 响应体超过大小上限
+    PayloadTooLarge,
+    // 读写快照等数据库操作失败
+    Database,
+    // 数据源配置不支持所选模式
+    Config,
+    // 读取本地文件失败
+    File,
+}
+
+impl FetchErrorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FetchErrorKind::Network => "network",
+            FetchErrorKind::HttpStatus => "http_status",
+            FetchErrorKind::Schema => "schema",
+            FetchErrorKind::PayloadTooLarge => "payload_too_large",
+            FetchErrorKind::Database => "database",
+            FetchErrorKind::Config => "config",
+            FetchErrorKind::File => "file",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct FetchError {
+    pub kind: FetchErrorKind,
+    pub status: Option<StatusCode>,
+    pub message: String,
+}
+
+impl FetchError {
+    pub fn new(kind: FetchErrorKind, message: String) -> Self {
+        FetchError { kind, status: None, message }
+    }
+
+    // 网络错误、429和5xx可能是短暂故障，值得重试
+    fn is_retryable(&self) -> bool {
+        match self.kind {
+            FetchErrorKind::Network => true,
+            FetchErrorKind::HttpStatus => self
+                .status
+                .is_some_and(|s| s.is_server_error() || s == StatusCode::TOO_MANY_REQUESTS),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+// 已读完响应体的响应
+pub struct FetchResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl FetchResponse {
+    // 非成功状态码转换为错误
+    pub fn ensure_success(self, what: &str) -> Result<Self, FetchError> {
+        if self.status.is_success() {
+            Ok(self)
+        } else {
+            Err(status_error(what, self.status))
+        }
+    }
+
+    pub fn header(&self, name: reqwest::header::HeaderName) -> Option<String> {
+        self.headers.get(name).and_then(|v| v.to_str().ok()).map(str::to_string)
+    }
+}
+
+lazy_static::lazy_static! {
+    // 同步共用的HTTP客户端，超时来自配置
+    static ref CLIENT: reqwest::Client = {
+        let policy = &SYNC_CONFIG.fetch;
+        reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(policy.connect_timeout_secs))
+            .read_timeout(Duration::from_secs(policy.read_timeout_secs))
+            .build()
+            .expect("创建HTTP客户端失败")
+    };
+}
+
+fn status_error(what: &str, status: StatusCode) -> FetchError {
+    FetchError {
+        kind: FetchErrorKind::HttpStatus,
+        status: Some(status),
+        message: format!("请求{} 失败: HTTP {}", what, status),
+    }
+}
+
+// reqwest 的错误信息不含底层原因，逐级拼接
+pub fn network_error(what: &str, e: reqwest::Error) -> FetchError {
+    let mut message = format!("请求{} 失败: {}", what, e);
+    let mut source = std::error::Error::source(&e);
+    while let Some(cause) = source {
+        message.push_str(&format!(": {}", cause));
+        source = cause.source();
+    }
+    FetchError::new(FetchErrorKind::Network, message)
+}
+
+// 第 attempt 次重试前的等待：指数增长，封顶后在后一半区间内随机
+pub fn backoff(attempt: u32) -> Duration {
+    let policy = &SYNC_CONFIG.fetch;
+    let delay = policy
+        .backoff_ms
+        .saturating_mul(1 << attempt.min(20))
+        .min(policy.max_backoff_ms);
+    Duration::from_millis(rand::thread_rng().gen_range(delay / 2..=delay))
+}
+
+// 发送一次请求并在大小上限内读完响应体，429和5xx视为失败以便重试
+async fn attempt(url: &str, headers: &HeaderMap, what: &str) -> Result<FetchResponse, FetchError> {
+    let max_bytes = SYNC_CONFIG.fetch.max_payload_bytes;
+    let too_large = || {
+        FetchError::new(
+            FetchErrorKind::PayloadTooLarge,
+            format!("{} 的响应超过大小上限 {} 字节", what, max_bytes),
+        )
+    };
+
+    let mut resp = CLIENT
+        .get(url)
+        .headers(headers.clone())
+        .send()
+        .await
+        .map_err(|e| network_error(what, e))?;
+    let status = resp.status();
+    if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+        return Err(status_error(what, status));
+    }
+    if resp.content_length().is_some_and(|len| len > max_bytes) {
+        return Err(too_large());
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = resp.chunk().await.map_err(|e| network_error(what, e))? {
+        if (body.len() + chunk.len()) as u64 > max_bytes {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(FetchResponse {
+        status,
+        headers: resp.headers().clone(),
+        body,
+    })
+}
+
+// 按配置的策略发送GET请求，短暂故障时退避重试
+// what 用于日志和错误信息，例如 "数据源 sthlm"
+pub async fn get(url: &str, headers: HeaderMap, what: &str) -> Result<FetchResponse, FetchError> {
+    let retries = SYNC_CONFIG.fetch.retries;
+    let mut retried = 0;
+    loop {
+        match attempt(url, &headers, what).await {
+            Ok(resp) => return Ok(resp),
+            Err(err) if err.is_retryable() && retried < retries => {
+                let delay = backoff(retried);
+                retried += 1;
+                println!("⚠️  {}，{} 毫秒后第 {} 次重试", err, delay.as_millis(), retried);
+                tokio::time::sleep(delay).await;
+            }
+            Err(mut err) => {
+                if retried > 0 {
+                    err.message = format!("{}（已重试 {} 次）", err.message, retried);
+                }
+                return Err(err);
+            }
+        }
+    }
+}
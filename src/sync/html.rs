@@ -0,0 +1,283 @@
+use crate::models::league::{GameInfo, LeaguePlayer, PlayerResult};
+use crate::sync::{description, DataCollection, DataGame, DataPlayer, DataResult, DataRoot};
+use chrono::{NaiveDate, NaiveDateTime};
+use crate::sync::fetch::{self, FetchError, FetchErrorKind};
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use scraper::{ElementRef, Html, Selector};
+use std::collections::HashMap;
+
+// 超过已记录的最大gid后，连续多少个gid不存在时认为已到最后一场比赛
+const MAX_CONSECUTIVE_MISSES: usize = 3;
+
+lazy_static::lazy_static! {
+    static ref ROW_SELECTOR: Selector = Selector::parse("tr.row").unwrap();
+    static ref CELL_SELECTOR: Selector = Selector::parse("td").unwrap();
+    static ref PARAM_SELECTOR: Selector = Selector::parse("td.param").unwrap();
+    static ref SEAT_SELECTOR: Selector = Selector::parse("td.seat").unwrap();
+    static ref NAME_SELECTOR: Selector = Selector::parse("td.name").unwrap();
+    static ref SCORE_SELECTOR: Selector = Selector::parse("td.score").unwrap();
+    static ref POSITION_SELECTOR: Selector = Selector::parse("td.position").unwrap();
+    static ref UMA_SELECTOR: Selector = Selector::parse("td.uma").unwrap();
+    static ref PENALTY_SELECTOR: Selector = Selector::parse("td.penalty").unwrap();
+    static ref TOTAL_SELECTOR: Selector = Selector::parse("td.total").unwrap();
+}
+
+fn cell_text(cell: ElementRef) -> String {
+    cell.text().collect::<String>().trim().to_string()
+}
+
+fn select_text(row: ElementRef, selector: &Selector) -> Option<String> {
+    row.select(selector).next().map(cell_text)
+}
+
+fn parse_number<T: std::str::FromStr>(row: ElementRef, selector: &Selector, field: &str) -> Result<T, String> {
+    let text = select_text(row, selector).ok_or_else(|| format!("缺少 {} 列", field))?;
+    text.parse::<T>().map_err(|_| format!("{} 列无法解析: '{}'", field, text))
+}
+
+// 解析registered时间，例如 "2025-02-12 23:28:40.256044549 UTC"
+fn parse_registered(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim_end_matches("UTC").trim();
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f").ok()
+}
+
+// 解析联赛网站的单场比赛页面（格式见 doc/html.txt）
+pub fn parse_game_page(html: &str) -> Result<GameInfo, String> {
+    let document = Html::parse_document(html);
+
+    // 参数表：第一列为参数名，第二列为值（值单元格的class有拼写错误，按位置取）
+    let mut params = HashMap::new();
+    let mut player_results = Vec::new();
+    for row in document.select(&ROW_SELECTOR) {
+        if row.select(&PARAM_SELECTOR).next().is_some() {
+            let cells: Vec<String> = row.select(&CELL_SELECTOR).map(cell_text).collect();
+            if let [name, value] = cells.as_slice() {
+                params.insert(name.to_lowercase(), value.clone());
+            }
+        } else if let Some(seat) = select_text(row, &SEAT_SELECTOR) {
+            let player_name = select_text(row, &NAME_SELECTOR).ok_or("缺少 Player 列")?;
+            player_results.push(PlayerResult {
+                seat,
+                player_name,
+                score: parse_number(row, &SCORE_SELECTOR, "Result")?,
+                position: parse_number(row, &POSITION_SELECTOR, "Position")?,
+                uma: parse_number(row, &UMA_SELECTOR, "Uma")?,
+                penalty: parse_number(row, &PENALTY_SELECTOR, "Penalty")?,
+                total: parse_number(row, &TOTAL_SELECTOR, "Total")?,
+            });
+        }
+    }
+
+    let game_id = params
+        .get("game id")
+        .and_then(|v| v.parse::<i32>().ok())
+        .ok_or("缺少或无法解析 Game id")?;
+    let played_date = params
+        .get("played")
+        .and_then(|v| NaiveDate::parse_from_str(v, "%Y-%m-%d").ok())
+        .ok_or("缺少或无法解析 played 日期")?;
+    let registered = params.get("registered").and_then(|v| parse_registered(v));
+    let description = params.get("description").cloned().unwrap_or_default();
+    let processed = params.get("processed").map(|v| v == "true").unwrap_or(false);
+    if player_results.is_empty() {
+        return Err("页面中没有玩家成绩".to_string());
+    }
+
+    // 解析失败的描述在写入时才产生警告，这里只取兜底值
+    let (parsed, _) = description::parse_or_fallback(&description, game_id as usize);
+    Ok(GameInfo {
+        game_id,
+        played_date,
+        registered,
+        description,
+        processed,
+        player_results,
+        season_num: parsed.season_num,
+        table_num: parsed.table_num,
+        group_name: parsed.group,
+        session_id: None,
+        source_gid: Some(game_id),
+    })
+}
+
+fn page_url(template: &str, gid: usize) -> String {
+    template.replace("{gid}", &gid.to_string())
+}
+
+// 拉取单场比赛页面，页面不存在时返回 None
+async fn fetch_game_page(template: &str, gid: usize) -> Result<Option<GameInfo>, FetchError> {
+    let url = page_url(template, gid);
+    let what = format!("比赛页面 {}", url);
+    let resp = fetch::get(&url, HeaderMap::new(), &what).await?;
+    if resp.status == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let resp = resp.ensure_success(&what)?;
+    let html = String::from_utf8_lossy(&resp.body);
+    parse_game_page(&html)
+        .map(Some)
+        .map_err(|e| FetchError::new(FetchErrorKind::Schema, format!("解析{} 失败: {}", what, e)))
+}
+
+// 按名字查找现有玩家，当前名字优先，其次是最近记录的曾用名
+pub fn player_index(players: Vec<LeaguePlayer>, aliases: Vec<(String, i32)>) -> HashMap<String, LeaguePlayer> {
+    let by_id: HashMap<i32, LeaguePlayer> = players.iter().map(|p| (p.id, p.clone())).collect();
+    let mut index: HashMap<String, LeaguePlayer> = players.into_iter().map(|p| (p.name.clone(), p)).collect();
+    for (name, player_id) in aliases {
+        if let Some(player) = by_id.get(&player_id) {
+            index.entry(name).or_insert_with(|| player.clone());
+        }
+    }
+    index
+}
+
+// 从gid 0开始逐页抓取，组装成与 data.json 相同的结构
+// 至少抓取到数据库中已记录的最大gid，之后连续若干页不存在时停止
+// 页面只有玩家名，不是上游pid：只按现有玩家的名字和曾用名匹配，不创建或改名玩家，
+// 有不认识的玩家的比赛不导入，记录警告
+pub async fn scrape_data_root(
+    template: &str,
+    known_players: &HashMap<String, LeaguePlayer>,
+    last_known_gid: Option<usize>,
+) -> Result<DataRoot, FetchError> {
+    let mut players = Vec::new();
+    let mut games = Vec::new();
+    let mut warnings = Vec::new();
+
+    let mut gid = 0;
+    // 当前连续不存在的gid
+    let mut misses = Vec::new();
+    loop {
+        let Some(info) = fetch_game_page(template, gid).await? else {
+            misses.push(gid);
+            if misses.len() >= MAX_CONSECUTIVE_MISSES && last_known_gid.is_none_or(|last| gid >= last) {
+                break;
+            }
+            gid += 1;
+            continue;
+        };
+        for missing in misses.drain(..) {
+            warnings.push(format!("比赛页面 gid {} 不存在，已跳过", missing));
+        }
+
+        let unknown: Vec<&str> = info
+            .player_results
+            .iter()
+            .map(|r| r.player_name.as_str())
+            .filter(|name| !known_players.contains_key(*name))
+            .collect();
+        if !unknown.is_empty() {
+            warnings.push(format!(
+                "比赛页面 gid {} ({}) 中的玩家 {} 不在数据库中，本场比赛未导入，请先用 mode=json 同步玩家",
+                gid,
+                info.description,
+                unknown.join("、")
+            ));
+            gid += 1;
+            continue;
+        }
+
+        let mut results = Vec::new();
+        for r in &info.player_results {
+            // 使用现有玩家的ID和当前名字，避免把曾用名写回
+            let player = &known_players[&r.player_name];
+            let pid = player.id as usize;
+            if !players.iter().any(|p: &DataPlayer| p.pid == pid) {
+                players.push(DataPlayer { pid, name: player.name.clone() });
+            }
+            results.push(DataResult {
+                player: pid,
+                result: r.score,
+                seat: r.seat.clone(),
+                uma: Some(r.uma),
+                position: u8::try_from(r.position).ok(),
+                penalty: Some(r.penalty),
+                total: Some(r.total),
+            });
+        }
+        games.push(DataGame {
+            gid,
+            played: info.played_date.format("%Y-%m-%d").to_string(),
+            description: info.description,
+            players: results.iter().map(|r| r.player).collect(),
+            results,
+            registered: info.registered,
+        });
+        gid += 1;
+    }
+    warnings.push(format!(
+        "gid {}-{} 的比赛页面连续不存在，抓取在此停止，之后的比赛不会导入",
+        misses[0], gid
+    ));
+    println!("从比赛页面抓取到 {} 场比赛，{} 名玩家", games.len(), players.len());
+
+    Ok(DataRoot {
+        collection: DataCollection {
+            players,
+            games,
+            sessions: Vec::new(),
+        },
+        warnings,
+    })
+}
+
+// 为 data.json 中的每场比赛补充页面上的 registered 时间
+pub async fn backfill_registered(template: &str, data: &mut DataRoot) -> Result<(), FetchError> {
+    let mut filled = 0;
+    for game in data.collection.games.iter_mut().filter(|g| g.registered.is_none()) {
+        if let Some(info) = fetch_game_page(template, game.gid).await? {
+            game.registered = info.registered;
+            filled += usize::from(info.registered.is_some());
+        }
+    }
+    println!("从比赛页面补充了 {} 场比赛的 registered 时间", filled);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_documented_game_page() {
+        let info = parse_game_page(include_str!("../../doc/html.txt")).unwrap();
+        assert_eq!(info.game_id, 28);
+        assert_eq!(info.source_gid, Some(28));
+        assert_eq!(info.description, "Season 0: Table 29");
+        assert_eq!((info.season_num, info.table_num), (0, 29));
+        assert_eq!(info.played_date, NaiveDate::from_ymd_opt(2025, 2, 12).unwrap());
+        let registered = NaiveDate::from_ymd_opt(2025, 2, 12)
+            .unwrap()
+            .and_hms_nano_opt(23, 28, 40, 256044549)
+            .unwrap();
+        assert_eq!(info.registered, Some(registered));
+        assert!(info.processed);
+
+        let results: Vec<_> = info
+            .player_results
+            .iter()
+            .map(|r| (r.seat.as_str(), r.player_name.as_str(), r.score, r.position, r.uma, r.penalty, r.total))
+            .collect();
+        assert_eq!(
+            results,
+            vec![
+                ("[E]", "Oskar R", 28.1, 1, 15.0, 0.0, 43.1),
+                ("[S]", "Ryan Ren", -25.8, 4, -15.0, 0.0, -40.8),
+                ("[W]", "CJ🍅", 8.9, 2, 5.0, 0.0, 13.9),
+                ("[N]", "Max", -11.2, 3, -5.0, 0.0, -16.2),
+            ]
+        );
+    }
+
+    #[test]
+    fn player_index_prefers_current_names_over_aliases() {
+        let players = vec![LeaguePlayer::new(1, "Max".to_string()), LeaguePlayer::new(2, "Maximilian".to_string())];
+        // 2 号玩家曾用名 Max，但已有玩家当前名为 Max
+        let aliases = vec![("Max".to_string(), 2), ("Maxi".to_string(), 2), ("Gone".to_string(), 9)];
+        let index = player_index(players, aliases);
+        assert_eq!(index["Max"].id, 1);
+        assert_eq!(index["Maxi"].name, "Maximilian");
+        assert!(!index.contains_key("Gone"));
+    }
+}
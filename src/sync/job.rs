@@ -0,0 +1,199 @@
+use crate::sync::{SyncError, SyncSummary, SYNC_STATE};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use tokio::sync::{broadcast, Mutex};
+
+// 内存中保留的已结束任务数量，更早的任务请查询 /sync/runs
+const FINISHED_JOBS_KEPT: usize = 20;
+
+// 同步任务所处阶段
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncPhase {
+    Pending,
+    Fetching,
+    Validating,
+    Players,
+    Sessions,
+    Games,
+    Reconciling,
+    Committing,
+    Finished,
+    Failed,
+    Cancelled,
+}
+
+impl SyncPhase {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, SyncPhase::Finished | SyncPhase::Failed | SyncPhase::Cancelled)
+    }
+}
+
+// 任务进度快照，任务ID即 sync_run 表中的运行记录ID
+#[derive(Debug, Clone, Serialize)]
+pub struct JobProgress {
+    pub job_id: i32,
+    pub source: String,
+    pub phase: SyncPhase,
+    pub current_gid: Option<usize>,
+    pub processed: usize,
+    pub total: usize,
+    pub warnings: Vec<String>,
+    pub message: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+// 推送给SSE订阅者的事件
+#[derive(Debug, Clone)]
+pub enum JobEvent {
+    Progress(JobProgress),
+    Warning(String),
+}
+
+impl JobEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            JobEvent::Progress(p) if p.phase.is_terminal() => "finished",
+            JobEvent::Progress(_) => "progress",
+            JobEvent::Warning(_) => "warning",
+        }
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, JobEvent::Progress(p) if p.phase.is_terminal())
+    }
+}
+
+struct Job {
+    progress: JobProgress,
+    events: broadcast::Sender<JobEvent>,
+}
+
+lazy_static::lazy_static! {
+    static ref JOBS: Mutex<HashMap<i32, Job>> = Mutex::new(HashMap::new());
+}
+
+// 登记新任务并清理过旧的已结束任务
+pub async fn register(job_id: i32, source: &str) -> JobHandle {
+    let mut jobs = JOBS.lock().await;
+    let (events, _) = broadcast::channel(256);
+    jobs.insert(job_id, Job {
+        progress: JobProgress {
+            job_id,
+            source: source.to_string(),
+            phase: SyncPhase::Pending,
+            current_gid: None,
+            processed: 0,
+            total: 0,
+            warnings: Vec::new(),
+            message: None,
+            started_at: Utc::now(),
+            finished_at: None,
+        },
+        events,
+    });
+
+    let mut finished: Vec<i32> = jobs
+        .iter()
+        .filter(|(_, job)| job.progress.phase.is_terminal())
+        .map(|(id, _)| *id)
+        .collect();
+    if finished.len() > FINISHED_JOBS_KEPT {
+        finished.sort_unstable();
+        for id in &finished[..finished.len() - FINISHED_JOBS_KEPT] {
+            jobs.remove(id);
+        }
+    }
+    JobHandle { id: job_id }
+}
+
+pub async fn progress(job_id: i32) -> Option<JobProgress> {
+    JOBS.lock().await.get(&job_id).map(|job| job.progress.clone())
+}
+
+// 返回当前进度并订阅后续事件，两者在同一把锁内获取，不会漏掉事件
+pub async fn subscribe(job_id: i32) -> Option<(JobProgress, broadcast::Receiver<JobEvent>)> {
+    let jobs = JOBS.lock().await;
+    jobs.get(&job_id)
+        .map(|job| (job.progress.clone(), job.events.subscribe()))
+}
+
+// 同步过程中用于更新任务进度的句柄
+#[derive(Debug, Clone, Copy)]
+pub struct JobHandle {
+    pub id: i32,
+}
+
+impl JobHandle {
+    async fn update(&self, f: impl FnOnce(&mut JobProgress)) {
+        let mut jobs = JOBS.lock().await;
+        if let Some(job) = jobs.get_mut(&self.id) {
+            f(&mut job.progress);
+            // 没有订阅者时发送失败，忽略即可
+            let _ = job.events.send(JobEvent::Progress(job.progress.clone()));
+        }
+    }
+
+    pub async fn set_phase(&self, phase: SyncPhase) {
+        self.update(|p| p.phase = phase).await;
+    }
+
+    pub async fn start_games(&self, total: usize) {
+        self.update(|p| {
+            p.phase = SyncPhase::Games;
+            p.total = total;
+        })
+        .await;
+    }
+
+    // 每处理完一场比赛调用一次，同时更新全局同步状态
+    pub async fn game_done(&self, gid: usize, processed: usize) {
+        {
+            let mut state = SYNC_STATE.lock().await;
+            state.current_id = gid;
+            state.success_count = processed;
+        }
+        self.update(|p| {
+            p.current_gid = Some(gid);
+            p.processed = processed;
+        })
+        .await;
+    }
+
+    // 是否已请求取消本任务
+    pub async fn cancel_requested(&self) -> bool {
+        let state = SYNC_STATE.lock().await;
+        state.cancel_requested && state.current_job == Some(self.id)
+    }
+
+    pub async fn warn(&self, message: &str) {
+        let mut jobs = JOBS.lock().await;
+        if let Some(job) = jobs.get_mut(&self.id) {
+            job.progress.warnings.push(message.to_string());
+            let _ = job.events.send(JobEvent::Warning(message.to_string()));
+        }
+    }
+
+    pub async fn finish(&self, result: &Result<SyncSummary, SyncError>) {
+        self.update(|p| {
+            p.finished_at = Some(Utc::now());
+            match result {
+                Ok(summary) => {
+                    p.phase = SyncPhase::Finished;
+                    p.message = Some(summary.message());
+                }
+                Err(err @ SyncError::Cancelled { .. }) => {
+                    p.phase = SyncPhase::Cancelled;
+                    p.message = Some(err.to_string());
+                }
+                Err(err) => {
+                    p.phase = SyncPhase::Failed;
+                    p.message = Some(err.to_string());
+                }
+            }
+        })
+        .await;
+    }
+}
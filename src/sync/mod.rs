@@ -0,0 +1,821 @@
+use crate::config::{ReconcileMode, SourceConfig, SYNC_CONFIG};
+use crate::db::{LeagueRepository, LeagueTransaction};
+use crate::sync::job::{JobHandle, SyncPhase};
+use crate::sync::fetch::FetchError;
+use crate::sync::snapshot::Fetched;
+use crate::sync::source::{DataSource, MemorySource};
+use crate::models::league::LeagueSession;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+pub mod apply;
+pub mod description;
+pub mod diff;
+pub mod fetch;
+pub mod html;
+pub mod job;
+pub mod reconcile;
+pub mod scheduler;
+pub mod snapshot;
+pub mod source;
+pub mod store;
+pub mod validate;
+pub mod webhook;
+
+// 定义同步状态结构体
+#[derive(Serialize)]
+pub struct SyncState {
+    pub is_running: bool,
+    // 正在运行的任务ID
+    pub current_job: Option<i32>,
+    pub current_id: usize,
+    pub success_count: usize,
+    // 已请求取消正在运行的任务，任务在下一场比赛前停止
+    pub cancel_requested: bool,
+    pub last_scheduled: Option<ScheduledRun>,
+    // 最近一次失败的同步，之后有同步成功时清除
+    pub last_error: Option<SyncFailure>,
+}
+
+impl SyncState {
+    // 任务结束后清除运行标记
+    fn clear_running(&mut self) {
+        self.is_running = false;
+        self.current_job = None;
+        self.cancel_requested = false;
+    }
+}
+
+// 失败的同步及其分类
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncFailure {
+    pub run_id: i32,
+    pub kind: &'static str,
+    pub message: String,
+    pub at: DateTime<Utc>,
+}
+
+// 最近一次定时同步的记录
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduledRun {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub status: ScheduledRunStatus,
+    pub message: String,
+    // 失败分类，成功时为空
+    pub error_kind: Option<&'static str>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduledRunStatus {
+    Success,
+    Failed,
+    // 已有同步在运行，本次跳过
+    Skipped,
+}
+
+// 全局共享状态
+lazy_static::lazy_static! {
+    pub static ref SYNC_STATE: Arc<Mutex<SyncState>> = Arc::new(Mutex::new(SyncState {
+        is_running: false,
+        current_job: None,
+        current_id: 0,
+        success_count: 0,
+        cancel_requested: false,
+        last_scheduled: None,
+        last_error: None,
+    }));
+}
+
+// 同步触发方式
+#[derive(Debug, Clone, Copy)]
+pub enum SyncTrigger {
+    Manual,
+    Scheduled,
+    // 通过 /sync/upload 上传数据
+    Upload,
+    // 命令行导入本地文件
+    Cli,
+    // 从存档的快照重新导入
+    Replay,
+}
+
+impl SyncTrigger {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SyncTrigger::Manual => "manual",
+            SyncTrigger::Scheduled => "scheduled",
+            SyncTrigger::Upload => "upload",
+            SyncTrigger::Cli => "cli",
+            SyncTrigger::Replay => "replay",
+        }
+    }
+}
+
+// 比赛数据的获取方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncMode {
+    // 拉取 data.json
+    #[default]
+    Json,
+    // 逐页抓取比赛页面，data.json 不可用时使用
+    Html,
+    // 拉取 data.json，再从比赛页面补充 registered 时间
+    Backfill,
+}
+
+impl SyncMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SyncMode::Json => "json",
+            SyncMode::Html => "html",
+            SyncMode::Backfill => "backfill",
+        }
+    }
+
+    // html/backfill 模式需要数据源配置比赛页面地址模板
+    pub fn check_source(&self, source: &SourceConfig) -> Result<(), String> {
+        if *self != SyncMode::Json && source.game_page_url.is_none() {
+            return Err(format!("数据源 {} 未配置比赛页面地址，无法使用 {} 模式", source.name, self.as_str()));
+        }
+        Ok(())
+    }
+}
+
+// 一次同步的统计结果
+#[derive(Debug, Default)]
+pub struct SyncSummary {
+    // 对应 sync_run 表中的运行记录
+    pub run_id: i32,
+    pub success_count: usize,
+    pub saved_count: usize,
+    pub updated_games_count: usize,
+    pub results_written_count: usize,
+    pub updated_players_count: usize,
+    pub created_players_count: usize,
+    pub sessions_written_count: usize,
+    // 上游已不存在、按对账配置处理的比赛桌和成绩数
+    pub orphan_tables_count: usize,
+    pub orphan_results_count: usize,
+    // 未通过校验、本次未写入的比赛数
+    pub quarantined_count: usize,
+    pub warnings: Vec<String>,
+    // 本次导入的快照
+    pub snapshot_id: Option<i32>,
+    // 数据源内容未变化，本次未导入
+    pub unchanged: bool,
+    // 写入前与数据库的差异，仅在配置了webhook时计算，用于通知
+    pub changes: Option<diff::SyncDiff>,
+}
+
+impl SyncSummary {
+    pub fn message(&self) -> String {
+        if self.unchanged {
+            return format!(
+                "数据源内容未变化（快照 {}），跳过同步（运行记录 {}）",
+                self.snapshot_id.unwrap_or_default(),
+                self.run_id
+            );
+        }
+        format!(
+            "强制同步触发成功（运行记录 {}），共处理{}场比赛，成功保存{}条记录，更新{}条记录，写入{}条成绩，写入{}个场次，新增{}名玩家，更新{}名玩家姓名，上游缺失{}张比赛桌和{}条成绩，隔离{}场未通过校验的比赛，警告{}条",
+            self.run_id,
+            self.success_count,
+            self.saved_count,
+            self.updated_games_count,
+            self.results_written_count,
+            self.sessions_written_count,
+            self.created_players_count,
+            self.updated_players_count,
+            self.orphan_tables_count,
+            self.orphan_results_count,
+            self.quarantined_count,
+            self.warnings.len()
+        )
+    }
+}
+
+#[derive(Debug)]
+pub enum SyncError {
+    // 已有同步在运行
+    AlreadyRunning { job_id: Option<i32>, current_id: usize, success_count: usize },
+    // 拉取或解析数据源失败
+    Fetch(FetchError),
+    // 开启或提交事务失败
+    Database(sqlx::Error),
+    // 玩家数据同步失败
+    Players(sqlx::Error),
+    // 场次数据同步失败
+    Sessions(sqlx::Error),
+    // 处理上游已删除的数据失败
+    Reconcile(sqlx::Error),
+    // 校验隔离失败
+    Quarantine(sqlx::Error),
+    // 某场比赛写入失败
+    Game { gid: usize, description: String, source: sqlx::Error },
+    // 收到取消请求，在比赛之间停止并回滚
+    Cancelled { processed: usize, total: usize },
+    // 任务异常中止（panic或任务被丢弃），事务已随连接回滚
+    Aborted,
+}
+
+impl SyncError {
+    // 失败分类，拉取失败按 FetchErrorKind 细分，写入失败均为 database
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SyncError::AlreadyRunning { .. } => "already_running",
+            SyncError::Fetch(e) => e.kind.as_str(),
+            SyncError::Cancelled { .. } => "cancelled",
+            SyncError::Aborted => "aborted",
+            _ => "database",
+        }
+    }
+}
+
+impl fmt::Display for SyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncError::AlreadyRunning { current_id, success_count, .. } => write!(
+                f,
+                "强制同步正在进行中，当前处理ID: {}，已成功处理: {} 个请求",
+                current_id, success_count
+            ),
+            SyncError::Fetch(e) => write!(f, "{}", e),
+            SyncError::Database(e) => write!(f, "同步失败，已回滚: 数据库错误: {}", e),
+            SyncError::Players(e) => write!(f, "同步失败，已回滚: 玩家数据同步失败: {}", e),
+            SyncError::Sessions(e) => write!(f, "同步失败，已回滚: 场次数据同步失败: {}", e),
+            SyncError::Reconcile(e) => write!(f, "同步失败，已回滚: 处理上游已删除的数据失败: {}", e),
+            SyncError::Quarantine(e) => write!(f, "同步失败，已回滚: 隔离未通过校验的比赛失败: {}", e),
+            SyncError::Game { gid, description, source } => write!(
+                f,
+                "同步失败，已回滚: 游戏 gid {} ({}) 处理失败: {}",
+                gid, description, source
+            ),
+            SyncError::Cancelled { processed, total } => write!(
+                f,
+                "同步已取消，已回滚: 取消前处理了 {}/{} 场比赛",
+                processed, total
+            ),
+            SyncError::Aborted => write!(f, "同步任务异常中止，已回滚"),
+        }
+    }
+}
+
+// 按同步模式获取数据，统一转换为 data.json 的结构
+pub async fn load_data(repo: &LeagueRepository, source: &SourceConfig, mode: SyncMode) -> Result<DataRoot, FetchError> {
+    source::for_mode(repo, source, mode, true)?.load().await
+}
+
+// 与 load_data 相同，但拉取的 data.json 不存档，用于不写数据库的预览
+pub async fn preview_data(repo: &LeagueRepository, source: &SourceConfig, mode: SyncMode) -> Result<DataRoot, FetchError> {
+    source::for_mode(repo, source, mode, false)?.load().await
+}
+
+// 同步任务的数据来源
+enum SyncInput {
+    // 从配置的数据源拉取
+    Source { source: SourceConfig, mode: SyncMode },
+    // 其他数据来源（上传、本地文件或快照），label 记录在运行记录的 source 中
+    Data { source: Box<dyn DataSource>, snapshot_id: Option<i32> },
+}
+
+impl SyncInput {
+    fn label(&self) -> &str {
+        match self {
+            SyncInput::Source { source, .. } => &source.name,
+            SyncInput::Data { source, .. } => source.label(),
+        }
+    }
+}
+
+// 已登记、尚未执行的同步任务
+pub struct SyncJob {
+    pub id: i32,
+    repo: LeagueRepository,
+    input: SyncInput,
+    // 任务登记后即生效，任务未执行就被丢弃时同样重置运行状态
+    guard: RunningGuard,
+}
+
+// 从数据源拉取并同步
+pub async fn start_sync(
+    repo: &LeagueRepository,
+    source: &SourceConfig,
+    mode: SyncMode,
+    trigger: SyncTrigger,
+) -> Result<SyncJob, SyncError> {
+    let input = SyncInput::Source { source: source.clone(), mode };
+    start_job(repo, input, trigger).await
+}
+
+// 从其他数据来源导入，与拉取的数据走同样的校验、玩家和比赛写入流程
+// 导入的数据可能是旧的存档，不做上游删除数据的对账
+pub async fn start_import(
+    repo: &LeagueRepository,
+    source: impl DataSource + 'static,
+    trigger: SyncTrigger,
+) -> Result<SyncJob, SyncError> {
+    let input = SyncInput::Data { source: Box::new(source), snapshot_id: None };
+    start_job(repo, input, trigger).await
+}
+
+// 从存档的快照重新导入，不需要网络，同样不做对账
+pub async fn start_replay(
+    repo: &LeagueRepository,
+    source: &str,
+    snapshot_id: i32,
+    data: DataRoot,
+) -> Result<SyncJob, SyncError> {
+    let input = SyncInput::Data {
+        source: Box::new(MemorySource::new(source, data)),
+        snapshot_id: Some(snapshot_id),
+    };
+    start_job(repo, input, SyncTrigger::Replay).await
+}
+
+// 标记同步为运行中并创建运行记录，运行记录ID即任务ID
+async fn start_job(repo: &LeagueRepository, input: SyncInput, trigger: SyncTrigger) -> Result<SyncJob, SyncError> {
+    // 持有状态锁直到运行记录创建完成，保证不会有两个任务同时开始
+    let mut state = SYNC_STATE.lock().await;
+
+    if state.is_running {
+        return Err(SyncError::AlreadyRunning {
+            job_id: state.current_job,
+            current_id: state.current_id,
+            success_count: state.success_count,
+        });
+    }
+
+    let run_id = repo
+        .create_sync_run(input.label(), trigger.as_str())
+        .await
+        .map_err(SyncError::Database)?;
+
+    // 标记为正在运行
+    state.is_running = true;
+    state.current_job = Some(run_id);
+    state.current_id = 0;
+    state.success_count = 0;
+    state.cancel_requested = false;
+
+    // 释放锁，这样其他请求可以查询状态
+    drop(state);
+
+    // 从这里开始调用方的future被丢弃（例如HTTP客户端断开）也会重置运行状态
+    let guard = RunningGuard {
+        job_id: run_id,
+        repo: repo.clone(),
+        armed: true,
+    };
+    job::register(run_id, input.label()).await;
+    Ok(SyncJob {
+        id: run_id,
+        repo: repo.clone(),
+        input,
+        guard,
+    })
+}
+
+// 任务未正常结束（panic、执行中或执行前被丢弃）时重置运行状态，并把运行记录标记为中止
+struct RunningGuard {
+    job_id: i32,
+    repo: LeagueRepository,
+    armed: bool,
+}
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        // Drop 中不能等待锁，交给运行时在后台完成
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let job_id = self.job_id;
+        let repo = self.repo.clone();
+        runtime.spawn(async move {
+            let err = SyncError::Aborted;
+            println!("❌ 同步任务 {} 异常中止，重置运行状态", job_id);
+            {
+                let mut state = SYNC_STATE.lock().await;
+                state.clear_running();
+                state.last_error = Some(SyncFailure {
+                    run_id: job_id,
+                    kind: err.kind(),
+                    message: err.to_string(),
+                    at: Utc::now(),
+                });
+            }
+            let finished = repo.finish_sync_run(job_id, "failed", &SyncSummary::default(), Some(&err)).await;
+            if let Err(e) = finished {
+                println!("❌ 写入运行记录 {} 失败: {}", job_id, e);
+            }
+            JobHandle { id: job_id }.finish(&Err(err)).await;
+        });
+    }
+}
+
+// 请求取消正在运行的任务，返回该任务ID，没有任务运行时返回 None
+pub async fn request_cancel() -> Option<i32> {
+    let mut state = SYNC_STATE.lock().await;
+    if !state.is_running {
+        return None;
+    }
+    state.cancel_requested = true;
+    state.current_job
+}
+
+impl SyncJob {
+    // 执行同步，webhook在后台投递
+    pub async fn run(self) -> Result<SyncSummary, SyncError> {
+        let (result, _deliveries) = self.execute().await;
+        result
+    }
+
+    // 执行同步并等待所有webhook投递结束（包括重试），用于执行完就退出的命令行导入
+    pub async fn run_and_wait_webhooks(self) -> Result<SyncSummary, SyncError> {
+        let (result, deliveries) = self.execute().await;
+        if !deliveries.is_empty() {
+            println!("等待 {} 个webhook投递完成...", deliveries.len());
+        }
+        for delivery in deliveries {
+            if let Err(e) = delivery.await {
+                println!("❌ webhook投递任务异常退出: {}", e);
+            }
+        }
+        result
+    }
+
+    async fn execute(self) -> (Result<SyncSummary, SyncError>, Vec<tokio::task::JoinHandle<()>>) {
+        let SyncJob { id, repo, input, mut guard } = self;
+        let handle = JobHandle { id };
+        let label = input.label().to_string();
+        let result = execute_run(&repo, input, handle).await;
+        guard.armed = false;
+
+        // 同步结束（无论成功与否），重置状态
+        {
+            let mut state = SYNC_STATE.lock().await;
+            state.clear_running();
+            state.last_error = match &result {
+                Ok(_) => None,
+                Err(err) => Some(SyncFailure {
+                    run_id: id,
+                    kind: err.kind(),
+                    message: err.to_string(),
+                    at: Utc::now(),
+                }),
+            };
+        }
+        handle.finish(&result).await;
+        match &result {
+            Ok(summary) => println!(
+                "强制同步完成，成功处理数: {}，成功保存数: {}",
+                summary.success_count, summary.saved_count
+            ),
+            Err(err) => println!("❌ {}", err),
+        }
+        let deliveries = match &result {
+            Ok(summary) if !summary.unchanged => webhook::dispatch(&repo, &label, summary),
+            _ => Vec::new(),
+        };
+        (result, deliveries)
+    }
+}
+
+// 执行一次完整同步并等待结束，定时任务使用
+pub async fn run_sync(
+    repo: &LeagueRepository,
+    source: &SourceConfig,
+    mode: SyncMode,
+    trigger: SyncTrigger,
+) -> Result<SyncSummary, SyncError> {
+    start_sync(repo, source, mode, trigger).await?.run().await
+}
+
+// 拉取并导入数据，最后写回运行结果
+async fn execute_run(repo: &LeagueRepository, input: SyncInput, job: JobHandle) -> Result<SyncSummary, SyncError> {
+    let run_id = job.id;
+    let label = input.label().to_string();
+    let result = match input {
+        SyncInput::Source { source, mode } => {
+            println!(
+                "开始强制同步过程（运行记录 {}），从数据源 {} ({}) 拉取数据，模式: {}...",
+                run_id, source.name, source.url, mode.as_str()
+            );
+
+            // 拉取数据并在单个事务中导入
+            job.set_phase(SyncPhase::Fetching).await;
+            if mode == SyncMode::Json {
+                sync_json_source(repo, &source, job).await
+            } else {
+                match load_data(repo, &source, mode).await {
+                    Ok(data) => import_data(repo, &data, reconcile::effective_mode(mode), job).await,
+                    Err(err) => Err(SyncError::Fetch(err)),
+                }
+            }
+        }
+        SyncInput::Data { source, snapshot_id } => {
+            job.set_phase(SyncPhase::Fetching).await;
+            match source.load().await {
+                Ok(data) => {
+                    println!(
+                        "开始导入 {} 的数据（运行记录 {}），共 {} 场比赛...",
+                        label, run_id, data.collection.games.len()
+                    );
+                    import_data(repo, &data, ReconcileMode::Off, job)
+                        .await
+                        .map(|summary| SyncSummary { snapshot_id, ..summary })
+                }
+                Err(err) => Err(SyncError::Fetch(err)),
+            }
+        }
+    };
+
+    // 失败时事务已回滚，记录的统计为零
+    let finished = match &result {
+        Ok(summary) => {
+            let status = if summary.unchanged { "unchanged" } else { "success" };
+            repo.finish_sync_run(run_id, status, summary, None).await
+        }
+        Err(err) => {
+            let status = if matches!(err, SyncError::Cancelled { .. }) { "cancelled" } else { "failed" };
+            repo.finish_sync_run(run_id, status, &SyncSummary::default(), Some(err)).await
+        }
+    };
+    if let Err(e) = finished {
+        println!("❌ 写入运行记录 {} 失败: {}", run_id, e);
+    }
+
+    result.map(|summary| SyncSummary { run_id, ..summary })
+}
+
+// 条件拉取 data.json，内容未变化且导入后没有放行的比赛时跳过导入
+async fn sync_json_source(
+    repo: &LeagueRepository,
+    source: &SourceConfig,
+    job: JobHandle,
+) -> Result<SyncSummary, SyncError> {
+    let fetched = snapshot::fetch(repo, source, true).await.map_err(SyncError::Fetch)?;
+    let snapshot_id = fetched.snapshot_id();
+    if let Fetched::Unchanged { .. } = fetched {
+        let released = repo
+            .quarantine_released_since_import(snapshot_id)
+            .await
+            .map_err(SyncError::Database)?;
+        if !released {
+            return Ok(SyncSummary {
+                snapshot_id: Some(snapshot_id),
+                unchanged: true,
+                ..SyncSummary::default()
+            });
+        }
+        println!("快照 {} 导入后有比赛被放行，重新导入", snapshot_id);
+    }
+
+    let data = fetched.into_data(repo).await.map_err(SyncError::Fetch)?;
+    let summary = import_data(repo, &data, reconcile::effective_mode(SyncMode::Json), job).await?;
+    if let Err(e) = repo.mark_snapshot_imported(snapshot_id).await {
+        println!("❌ 标记快照 {} 已导入失败: {}", snapshot_id, e);
+    }
+    Ok(SyncSummary { snapshot_id: Some(snapshot_id), ..summary })
+}
+
+// 在单个事务中导入数据，任一写入失败则整体回滚
+async fn import_data(
+    repo: &LeagueRepository,
+    data: &DataRoot,
+    reconcile: ReconcileMode,
+    job: JobHandle,
+) -> Result<SyncSummary, SyncError> {
+    let mut tx = repo.begin().await.map_err(SyncError::Database)?;
+    let mut summary = SyncSummary::default();
+
+    match import_in_tx(&mut tx, data, reconcile, &mut summary, job).await {
+        Ok(()) => {
+            job.set_phase(SyncPhase::Committing).await;
+            tx.commit().await.map_err(SyncError::Database)?;
+            Ok(summary)
+        }
+        Err(err) => {
+            if let Err(e) = tx.rollback().await {
+                println!("❌ 回滚事务失败: {}", e);
+            }
+            Err(err)
+        }
+    }
+}
+
+async fn import_in_tx(
+    tx: &mut LeagueTransaction,
+    data: &DataRoot,
+    reconcile: ReconcileMode,
+    summary: &mut SyncSummary,
+    job: JobHandle,
+) -> Result<(), SyncError> {
+    // webhook需要列出本次新增和变化的内容，在写入前对比
+    if !SYNC_CONFIG.webhooks.is_empty() {
+        summary.changes = Some(diff::build_diff(tx, data, reconcile).await.map_err(SyncError::Database)?);
+    }
+
+    summary.warnings.extend(data.warnings.iter().cloned());
+
+    // 写入前校验所有比赛，未通过的放入隔离表，不写入
+    job.set_phase(SyncPhase::Validating).await;
+    let games = validate_and_quarantine(tx, data, summary).await.map_err(SyncError::Quarantine)?;
+    for warning in &summary.warnings {
+        job.warn(warning).await;
+    }
+
+    job.set_phase(SyncPhase::Players).await;
+    let warnings_before = summary.warnings.len();
+    let mut maps = apply::sync_players(tx, data, summary).await.map_err(SyncError::Players)?;
+    for warning in &summary.warnings[warnings_before..] {
+        job.warn(warning).await;
+    }
+
+    job.set_phase(SyncPhase::Sessions).await;
+    let warnings_before = summary.warnings.len();
+    let game_sessions = sync_sessions(tx, data, summary).await.map_err(SyncError::Sessions)?;
+    for warning in &summary.warnings[warnings_before..] {
+        job.warn(warning).await;
+    }
+
+    job.start_games(games.len()).await;
+    for game in &games {
+        // 只在比赛之间响应取消，已写入的部分随事务回滚
+        if job.cancel_requested().await {
+            return Err(SyncError::Cancelled {
+                processed: summary.success_count,
+                total: games.len(),
+            });
+        }
+        let warnings_before = summary.warnings.len();
+        let session_id = game_sessions.get(&game.gid).copied();
+        apply::sync_game(tx, data, game, session_id, &mut maps, summary)
+            .await
+            .map_err(|source| SyncError::Game {
+                gid: game.gid,
+                description: game.description.clone(),
+                source,
+            })?;
+
+        summary.success_count += 1;
+        // 推送本场产生的警告并更新进度
+        for warning in &summary.warnings[warnings_before..] {
+            job.warn(warning).await;
+        }
+        job.game_done(game.gid, summary.success_count).await;
+    }
+
+    // 处理上游已不存在的比赛桌和成绩
+    if reconcile != ReconcileMode::Off {
+        job.set_phase(SyncPhase::Reconciling).await;
+        let quarantined: HashSet<usize> = data
+            .collection
+            .games
+            .iter()
+            .map(|g| g.gid)
+            .filter(|gid| !games.iter().any(|g| g.gid == *gid))
+            .collect();
+        let orphans = reconcile::find_orphans(tx, data, &maps.name_id, &quarantined)
+            .await
+            .map_err(SyncError::Reconcile)?;
+        reconcile::apply(tx, &orphans, reconcile)
+            .await
+            .map_err(SyncError::Reconcile)?;
+        summary.orphan_tables_count = orphans.tables.len();
+        summary.orphan_results_count = orphans.results.len();
+        println!(
+            "上游缺失 {} 张比赛桌、{} 条成绩，处理方式: {}",
+            orphans.tables.len(),
+            orphans.results.len(),
+            reconcile.as_str()
+        );
+    }
+    Ok(())
+}
+
+// 校验所有比赛并更新隔离表，返回可以写入的比赛
+async fn validate_and_quarantine<'a>(
+    tx: &mut LeagueTransaction,
+    data: &'a DataRoot,
+    summary: &mut SyncSummary,
+) -> Result<Vec<&'a DataGame>, sqlx::Error> {
+    let released = tx.released_quarantine_gids().await?;
+    let (valid, invalid) = validate::partition(&data.collection.games, &released);
+
+    let valid_gids: Vec<i32> = valid.iter().map(|game| game.gid as i32).collect();
+    tx.clear_quarantine(&valid_gids).await?;
+    for (game, reasons) in &invalid {
+        let payload = serde_json::to_value(game).unwrap_or_default();
+        tx.quarantine_game(game.gid as i32, &game.description, reasons, payload).await?;
+        summary.warnings.push(format!(
+            "游戏 {} ({}) 未通过校验，已隔离: {}",
+            game.gid,
+            game.description,
+            reasons.join("；")
+        ));
+    }
+    summary.quarantined_count = invalid.len();
+    if !invalid.is_empty() {
+        println!("⚠️  {} 场比赛未通过校验，已放入隔离表", invalid.len());
+    }
+    Ok(valid)
+}
+
+// 写入所有场次，返回 gid -> 场次ID 的映射
+async fn sync_sessions(
+    tx: &mut LeagueTransaction,
+    data: &DataRoot,
+    summary: &mut SyncSummary,
+) -> Result<HashMap<usize, i32>, sqlx::Error> {
+    let mut game_sessions = HashMap::new();
+    for session in &data.collection.sessions {
+        let date = NaiveDate::parse_from_str(&session.date, "%Y-%m-%d").ok();
+        if date.is_none() {
+            summary.warnings.push(format!("场次 {} 的日期无法解析: '{}'", session.sid, session.date));
+        }
+        tx.upsert_session(&LeagueSession {
+            id: session.sid as i32,
+            name: session.name.clone(),
+            group_name: session.group.clone(),
+            date,
+        })
+        .await?;
+        summary.sessions_written_count += 1;
+
+        for gid in &session.games {
+            if let Some(previous) = game_sessions.insert(*gid, session.sid as i32) {
+                summary.warnings.push(format!(
+                    "游戏 {} 同时属于场次 {} 和 {}，使用后者",
+                    gid, previous, session.sid
+                ));
+            }
+        }
+    }
+    println!("写入了 {} 个场次", summary.sessions_written_count);
+    Ok(game_sessions)
+}
+
+// 适配 data.json 的结构体，HTML抓取的数据也转换为该结构
+#[derive(Debug, Clone, Deserialize)]
+pub struct DataRoot {
+    pub collection: DataCollection,
+    // 加载数据时产生的警告（例如抓取页面时跳过的比赛），导入时记入运行结果
+    #[serde(skip)]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DataCollection {
+    pub players: Vec<DataPlayer>,
+    pub games: Vec<DataGame>,
+    pub sessions: Vec<DataSession>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DataPlayer {
+    pub pid: usize,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataGame {
+    pub gid: usize,
+    pub played: String,
+    pub description: String,
+    #[allow(dead_code)]
+    pub players: Vec<usize>,
+    pub results: Vec<DataResult>,
+    // data.json 不含该字段，仅由比赛页面提供
+    #[serde(default)]
+    pub registered: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataResult {
+    pub player: usize,
+    pub result: f64,
+    pub seat: String,
+    pub uma: Option<f64>,
+    pub position: Option<u8>,
+    pub penalty: Option<f64>,
+    pub total: Option<f64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DataSession {
+    pub sid: usize,
+    pub name: String,
+    pub group: String,
+    pub date: String,
+    pub games: Vec<usize>,
+}
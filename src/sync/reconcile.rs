@@ -0,0 +1,91 @@
+use crate::config::{ReconcileMode, SYNC_CONFIG};
+use crate::db::LeagueTransaction;
+use crate::models::sync::{OrphanResult, OrphanTable};
+use crate::sync::{DataRoot, SyncMode};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+// 上游已不存在的比赛桌和成绩
+#[derive(Debug, Default, Serialize)]
+pub struct Orphans {
+    pub tables: Vec<OrphanTable>,
+    pub results: Vec<OrphanResult>,
+}
+
+// 本次同步实际使用的对账方式
+// html 模式在连续缺页时停止抓取，拿到的不一定是全部比赛，因此不做对账
+pub fn effective_mode(mode: SyncMode) -> ReconcileMode {
+    match mode {
+        SyncMode::Html => ReconcileMode::Off,
+        _ => SYNC_CONFIG.reconcile,
+    }
+}
+
+// 对比数据库与上游数据，找出上游已不存在的比赛桌，以及上游比赛中已不存在的玩家成绩
+// name_id 为玩家名到数据库ID的映射；quarantined 中的比赛未写入，不检查其成绩
+pub async fn find_orphans(
+    tx: &mut LeagueTransaction,
+    data: &DataRoot,
+    name_id: &HashMap<String, i32>,
+    quarantined: &HashSet<usize>,
+) -> Result<Orphans, sqlx::Error> {
+    let pid_name: HashMap<usize, &str> = data
+        .collection
+        .players
+        .iter()
+        .map(|p| (p.pid, p.name.as_str()))
+        .collect();
+
+    // 每场上游比赛中玩家的数据库ID
+    let mut expected: HashMap<i32, HashSet<i32>> = HashMap::new();
+    for game in &data.collection.games {
+        let players = game
+            .results
+            .iter()
+            .filter_map(|r| pid_name.get(&r.player))
+            .filter_map(|name| name_id.get(*name).copied())
+            .collect();
+        expected.insert(game.gid as i32, players);
+    }
+
+    let tables = tx
+        .list_linked_tables()
+        .await?
+        .into_iter()
+        .filter(|t| !expected.contains_key(&t.source_gid))
+        .collect();
+    let results = tx
+        .list_linked_results()
+        .await?
+        .into_iter()
+        .filter(|r| {
+            !quarantined.contains(&(r.source_gid as usize))
+                && expected
+                    .get(&r.source_gid)
+                    .is_some_and(|players| !players.contains(&r.player_id))
+        })
+        .collect();
+    Ok(Orphans { tables, results })
+}
+
+// 按配置处理找到的记录
+pub async fn apply(tx: &mut LeagueTransaction, orphans: &Orphans, mode: ReconcileMode) -> Result<(), sqlx::Error> {
+    let table_ids: Vec<i32> = orphans.tables.iter().map(|t| t.id).collect();
+    let result_ids: Vec<i32> = orphans.results.iter().map(|r| r.id).collect();
+    match mode {
+        ReconcileMode::Off => {}
+        ReconcileMode::Flag => {
+            tx.flag_tables(&table_ids).await?;
+            tx.flag_results(&result_ids).await?;
+        }
+        ReconcileMode::SoftDelete => {
+            tx.soft_delete_tables(&table_ids).await?;
+            tx.soft_delete_results(&result_ids).await?;
+        }
+        ReconcileMode::Delete => {
+            tx.delete_tables(&table_ids).await?;
+            tx.delete_results(&result_ids).await?;
+        }
+    }
+    Ok(())
+}
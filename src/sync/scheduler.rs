@@ -0,0 +1,87 @@
+use crate::config::{ScheduleConfig, SourceConfig, SYNC_CONFIG};
+use crate::db::LeagueRepository;
+use crate::sync::{run_sync, ScheduledRun, ScheduledRunStatus, SyncError, SyncMode, SyncTrigger, SYNC_STATE};
+use chrono::Utc;
+use cron::Schedule;
+use std::str::FromStr;
+use std::time::Duration;
+
+// 定时触发方式
+enum Trigger {
+    Interval(Duration),
+    Cron(Box<Schedule>),
+}
+
+impl Trigger {
+    fn from_config(config: &ScheduleConfig) -> Option<Self> {
+        if let Some(secs) = config.interval_secs {
+            return Some(Trigger::Interval(Duration::from_secs(secs)));
+        }
+        // cron表达式已在加载配置时校验
+        config
+            .cron
+            .as_deref()
+            .and_then(|expr| Schedule::from_str(expr).ok())
+            .map(|schedule| Trigger::Cron(Box::new(schedule)))
+    }
+
+    // 距离下一次触发的等待时间
+    fn next_delay(&self) -> Option<Duration> {
+        match self {
+            Trigger::Interval(interval) => Some(*interval),
+            Trigger::Cron(schedule) => {
+                let next = schedule.upcoming(Utc).next()?;
+                Some((next - Utc::now()).to_std().unwrap_or_default())
+            }
+        }
+    }
+}
+
+// 启动后台定时同步任务，未配置时不启动
+pub fn spawn(repo: LeagueRepository) {
+    let config = &SYNC_CONFIG.schedule;
+    let Some(trigger) = Trigger::from_config(config) else {
+        println!("未配置定时同步");
+        return;
+    };
+    let Some(source) = SYNC_CONFIG.source(config.source.as_deref()) else {
+        println!("定时同步数据源未找到，不启动定时同步");
+        return;
+    };
+
+    tokio::spawn(async move {
+        while let Some(delay) = trigger.next_delay() {
+            println!("下一次定时同步将在 {} 秒后执行", delay.as_secs());
+            tokio::time::sleep(delay).await;
+            run_scheduled(&repo, source).await;
+        }
+        println!("cron表达式没有后续触发时间，定时同步结束");
+    });
+}
+
+// 执行一次定时同步并记录结果
+// 同步在单独的任务中运行，panic 只记为本次失败，定时循环继续
+async fn run_scheduled(repo: &LeagueRepository, source: &'static SourceConfig) {
+    let started_at = Utc::now();
+    let task_repo = repo.clone();
+    let handle = tokio::spawn(async move {
+        run_sync(&task_repo, source, SyncMode::Json, SyncTrigger::Scheduled).await
+    });
+    let (status, message, error_kind) = match handle.await {
+        Ok(Ok(summary)) => (ScheduledRunStatus::Success, summary.message(), None),
+        // 与手动同步重叠时跳过本次
+        Ok(Err(err @ SyncError::AlreadyRunning { .. })) => (ScheduledRunStatus::Skipped, err.to_string(), None),
+        Ok(Err(err)) => (ScheduledRunStatus::Failed, err.to_string(), Some(err.kind())),
+        Err(err) => (ScheduledRunStatus::Failed, format!("同步任务异常退出: {}", err), Some(SyncError::Aborted.kind())),
+    };
+    println!("定时同步结束 ({:?}): {}", status, message);
+
+    let mut state = SYNC_STATE.lock().await;
+    state.last_scheduled = Some(ScheduledRun {
+        started_at,
+        finished_at: Utc::now(),
+        status,
+        message,
+        error_kind,
+    });
+}
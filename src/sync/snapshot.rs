@@ -0,0 +1,145 @@
+use crate::config::SourceConfig;
+use crate::db::LeagueRepository;
+use crate::sync::fetch::{self, FetchError, FetchErrorKind};
+use crate::sync::DataRoot;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use reqwest::header::{self, HeaderMap, HeaderValue};
+use reqwest::StatusCode;
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+
+// 拉取结果，无论是否变化都对应一个已存档的快照
+pub enum Fetched {
+    // 数据源返回304，或内容与上次导入的快照相同
+    Unchanged { snapshot_id: i32 },
+    Changed { snapshot_id: i32, data: DataRoot },
+}
+
+impl Fetched {
+    pub fn snapshot_id(&self) -> i32 {
+        match self {
+            Fetched::Unchanged { snapshot_id } | Fetched::Changed { snapshot_id, .. } => *snapshot_id,
+        }
+    }
+
+    // 取出数据，未变化时从快照中读取
+    pub async fn into_data(self, repo: &LeagueRepository) -> Result<DataRoot, FetchError> {
+        match self {
+            Fetched::Changed { data, .. } => Ok(data),
+            Fetched::Unchanged { snapshot_id } => load(repo, snapshot_id)
+                .await
+                .and_then(|data| data.ok_or_else(|| format!("快照 {} 不存在", snapshot_id)))
+                .map_err(|msg| FetchError::new(FetchErrorKind::Database, msg)),
+        }
+    }
+}
+
+fn content_hash(body: &[u8]) -> String {
+    hex::encode(Sha256::digest(body))
+}
+
+fn compress(body: &[u8]) -> Result<Vec<u8>, String> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(body)
+        .and_then(|_| encoder.finish())
+        .map_err(|e| format!("压缩快照失败: {}", e))
+}
+
+fn decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+    GzDecoder::new(data)
+        .read_to_end(&mut body)
+        .map_err(|e| format!("解压快照失败: {}", e))?;
+    Ok(body)
+}
+
+// 拉取数据源的 data.json 并存档
+// conditional 为 true 时带上次导入快照的 ETag/Last-Modified 发送条件请求，未变化时不解析数据
+pub async fn fetch(repo: &LeagueRepository, source: &SourceConfig, conditional: bool) -> Result<Fetched, FetchError> {
+    let what = format!("数据源 {}", source.name);
+    let db_error = |e: sqlx::Error| FetchError::new(FetchErrorKind::Database, format!("读写{} 的快照失败: {}", what, e));
+    let previous = if conditional {
+        repo.latest_imported_snapshot(&source.name).await.map_err(db_error)?
+    } else {
+        None
+    };
+
+    let mut headers = HeaderMap::new();
+    if let Some(prev) = &previous {
+        if let Some(etag) = prev.etag.as_deref().and_then(|v| HeaderValue::from_str(v).ok()) {
+            headers.insert(header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = prev.last_modified.as_deref().and_then(|v| HeaderValue::from_str(v).ok()) {
+            headers.insert(header::IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+    let resp = fetch::get(&source.url, headers, &what).await?;
+
+    if resp.status == StatusCode::NOT_MODIFIED
+        && let Some(prev) = &previous
+    {
+        println!("{} 返回304，内容未变化", what);
+        let _ = repo.touch_snapshot(prev.id).await;
+        return Ok(Fetched::Unchanged { snapshot_id: prev.id });
+    }
+    let resp = resp.ensure_success(&what)?;
+    let etag = resp.header(header::ETAG);
+    let last_modified = resp.header(header::LAST_MODIFIED);
+
+    let hash = content_hash(&resp.body);
+    if let Some(prev) = &previous
+        && prev.content_hash == hash
+    {
+        println!("{} 的内容哈希未变化", what);
+        let _ = repo.touch_snapshot(prev.id).await;
+        return Ok(Fetched::Unchanged { snapshot_id: prev.id });
+    }
+
+    // 先解析，无效的数据不存档
+    let data = parse(&resp.body, &what)?;
+    let compressed = compress(&resp.body).map_err(|msg| FetchError::new(FetchErrorKind::Database, msg))?;
+    let snapshot_id = repo
+        .save_snapshot(
+            &source.name,
+            &hash,
+            etag.as_deref(),
+            last_modified.as_deref(),
+            resp.body.len() as i32,
+            &compressed,
+        )
+        .await
+        .map_err(db_error)?;
+    Ok(Fetched::Changed { snapshot_id, data })
+}
+
+// 只拉取并解析，不存档也不发送条件请求，预览用
+pub async fn fetch_unarchived(source: &SourceConfig) -> Result<DataRoot, FetchError> {
+    let what = format!("数据源 {}", source.name);
+    let resp = fetch::get(&source.url, HeaderMap::new(), &what).await?.ensure_success(&what)?;
+    parse(&resp.body, &what)
+}
+
+fn parse(body: &[u8], what: &str) -> Result<DataRoot, FetchError> {
+    serde_json::from_slice::<DataRoot>(body)
+        .map_err(|e| FetchError::new(FetchErrorKind::Schema, format!("解析{} 的JSON失败: {}", what, e)))
+}
+
+// 读取快照的原始JSON，快照不存在时返回 None
+pub async fn load_raw(repo: &LeagueRepository, id: i32) -> Result<Option<Vec<u8>>, String> {
+    let data = repo
+        .get_snapshot_data(id)
+        .await
+        .map_err(|e| format!("读取快照 {} 失败: {}", id, e))?;
+    data.map(|d| decompress(&d)).transpose()
+}
+
+// 读取并解析快照，不需要网络
+pub async fn load(repo: &LeagueRepository, id: i32) -> Result<Option<DataRoot>, String> {
+    let Some(body) = load_raw(repo, id).await? else {
+        return Ok(None);
+    };
+    serde_json::from_slice::<DataRoot>(&body)
+        .map(Some)
+        .map_err(|e| format!("解析快照 {} 的JSON失败: {}", id, e))
+}
@@ -0,0 +1,180 @@
+use crate::config::SourceConfig;
+use crate::db::LeagueRepository;
+use crate::sync::fetch::{FetchError, FetchErrorKind};
+use crate::sync::{html, snapshot, DataRoot, SyncMode};
+use async_trait::async_trait;
+
+// 同步数据的来源，统一产出 data.json 的结构，导入流程不关心数据从哪里来
+#[async_trait]
+pub trait DataSource: Send + Sync {
+    // 记录在运行记录 source 中的名称
+    fn label(&self) -> &str;
+
+    async fn load(&self) -> Result<DataRoot, FetchError>;
+}
+
+// 按同步模式选择配置数据源的实现，archive 为 false 时拉取的 data.json 不存档为快照
+pub fn for_mode(
+    repo: &LeagueRepository,
+    source: &SourceConfig,
+    mode: SyncMode,
+    archive: bool,
+) -> Result<Box<dyn DataSource>, FetchError> {
+    mode.check_source(source)
+        .map_err(|msg| FetchError::new(FetchErrorKind::Config, msg))?;
+    let template = source.game_page_url.clone().unwrap_or_default();
+    let http = || {
+        let http = HttpSource::new(repo, source);
+        if archive { http } else { http.without_archive() }
+    };
+    Ok(match mode {
+        SyncMode::Json => Box::new(http()),
+        SyncMode::Html => Box::new(HtmlSource::new(repo, &source.name, template)),
+        SyncMode::Backfill => Box::new(BackfillSource::new(Box::new(http()), template)),
+    })
+}
+
+// 通过HTTP拉取 data.json，默认每次拉取的内容存档为快照
+pub struct HttpSource {
+    repo: LeagueRepository,
+    source: SourceConfig,
+    archive: bool,
+}
+
+impl HttpSource {
+    pub fn new(repo: &LeagueRepository, source: &SourceConfig) -> Self {
+        HttpSource { repo: repo.clone(), source: source.clone(), archive: true }
+    }
+
+    // 预览用，不写快照
+    pub fn without_archive(self) -> Self {
+        HttpSource { archive: false, ..self }
+    }
+}
+
+#[async_trait]
+impl DataSource for HttpSource {
+    fn label(&self) -> &str {
+        &self.source.name
+    }
+
+    async fn load(&self) -> Result<DataRoot, FetchError> {
+        if !self.archive {
+            return snapshot::fetch_unarchived(&self.source).await;
+        }
+        snapshot::fetch(&self.repo, &self.source, false)
+            .await?
+            .into_data(&self.repo)
+            .await
+    }
+}
+
+// 本地的 data.json 文件
+pub struct FileSource {
+    path: String,
+    label: String,
+}
+
+impl FileSource {
+    pub fn new(path: &str) -> Self {
+        FileSource { path: path.to_string(), label: format!("file:{}", path) }
+    }
+}
+
+#[async_trait]
+impl DataSource for FileSource {
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    async fn load(&self) -> Result<DataRoot, FetchError> {
+        let content = tokio::fs::read(&self.path).await.map_err(|e| {
+            FetchError::new(FetchErrorKind::File, format!("读取文件 {} 失败: {}", self.path, e))
+        })?;
+        serde_json::from_slice(&content).map_err(|e| {
+            FetchError::new(FetchErrorKind::Schema, format!("解析文件 {} 的JSON失败: {}", self.path, e))
+        })
+    }
+}
+
+// 已在内存中的数据，例如上传的请求体、快照或测试夹具
+pub struct MemorySource {
+    label: String,
+    data: DataRoot,
+}
+
+impl MemorySource {
+    pub fn new(label: &str, data: DataRoot) -> Self {
+        MemorySource { label: label.to_string(), data }
+    }
+}
+
+#[async_trait]
+impl DataSource for MemorySource {
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    async fn load(&self) -> Result<DataRoot, FetchError> {
+        Ok(self.data.clone())
+    }
+}
+
+// 逐页抓取比赛页面
+pub struct HtmlSource {
+    repo: LeagueRepository,
+    label: String,
+    template: String,
+}
+
+impl HtmlSource {
+    pub fn new(repo: &LeagueRepository, label: &str, template: String) -> Self {
+        HtmlSource { repo: repo.clone(), label: label.to_string(), template }
+    }
+}
+
+#[async_trait]
+impl DataSource for HtmlSource {
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    async fn load(&self) -> Result<DataRoot, FetchError> {
+        // 页面只有玩家名，需要用现有玩家和曾用名确定ID
+        let db_error = |e: sqlx::Error| FetchError::new(FetchErrorKind::Database, format!("获取现有玩家失败: {}", e));
+        let players = self.repo.list_players().await.map_err(db_error)?;
+        let aliases = self.repo.list_player_aliases().await.map_err(db_error)?;
+        let last_gid = self
+            .repo
+            .max_source_gid()
+            .await
+            .map_err(|e| FetchError::new(FetchErrorKind::Database, format!("获取已记录的最大gid失败: {}", e)))?;
+        let known_players = html::player_index(players, aliases);
+        html::scrape_data_root(&self.template, &known_players, last_gid.map(|gid| gid as usize)).await
+    }
+}
+
+// 从其他数据源加载后，用比赛页面补充 registered 时间
+pub struct BackfillSource {
+    inner: Box<dyn DataSource>,
+    template: String,
+}
+
+impl BackfillSource {
+    pub fn new(inner: Box<dyn DataSource>, template: String) -> Self {
+        BackfillSource { inner, template }
+    }
+}
+
+#[async_trait]
+impl DataSource for BackfillSource {
+    fn label(&self) -> &str {
+        self.inner.label()
+    }
+
+    async fn load(&self) -> Result<DataRoot, FetchError> {
+        let mut data = self.inner.load().await?;
+        html::backfill_registered(&self.template, &mut data).await?;
+        Ok(data)
+    }
+}
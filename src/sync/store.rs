@@ -0,0 +1,168 @@
+use crate::db::LeagueTransaction;
+use crate::models::league::{LeagueGame, LeaguePlayer, LeagueResult};
+use async_trait::async_trait;
+use sqlx::Error;
+use std::collections::HashMap;
+
+// 写入玩家、比赛和成绩所需的存储操作
+// 同步时由事务实现，也可以用内存实现对照夹具检查写入逻辑
+#[async_trait]
+pub trait SyncStore: Send {
+    async fn list_players(&mut self) -> Result<Vec<LeaguePlayer>, Error>;
+    // 已合并的玩家ID -> 合并目标ID
+    async fn merged_player_ids(&mut self) -> Result<HashMap<i32, i32>, Error>;
+    // 自动分配ID，返回新ID
+    async fn create_player(&mut self, player: &LeaguePlayer) -> Result<i32, Error>;
+    async fn create_player_with_id(&mut self, player: &LeaguePlayer) -> Result<i32, Error>;
+    async fn update_player(&mut self, player: &LeaguePlayer) -> Result<(), Error>;
+
+    async fn get_game_by_source_gid(&mut self, source_gid: i32) -> Result<Option<LeagueGame>, Error>;
+    // 尚未记录上游gid的旧记录
+    async fn get_legacy_game_by_season_and_table(&mut self, season_num: i32, table_num: i32) -> Result<Option<LeagueGame>, Error>;
+    async fn create_game(&mut self, game: &LeagueGame) -> Result<i32, Error>;
+    async fn update_game(&mut self, game: &LeagueGame) -> Result<(), Error>;
+
+    async fn get_result_by_table_and_player(&mut self, table_id: i32, player_id: i32) -> Result<Option<LeagueResult>, Error>;
+    async fn create_result(&mut self, result: &LeagueResult) -> Result<(), Error>;
+    async fn update_result(&mut self, result: &LeagueResult) -> Result<(), Error>;
+}
+
+#[async_trait]
+impl SyncStore for LeagueTransaction {
+    async fn list_players(&mut self) -> Result<Vec<LeaguePlayer>, Error> {
+        LeagueTransaction::list_players(self).await
+    }
+
+    async fn merged_player_ids(&mut self) -> Result<HashMap<i32, i32>, Error> {
+        LeagueTransaction::merged_player_ids(self).await
+    }
+
+    async fn create_player(&mut self, player: &LeaguePlayer) -> Result<i32, Error> {
+        LeagueTransaction::create_player(self, player).await
+    }
+
+    async fn create_player_with_id(&mut self, player: &LeaguePlayer) -> Result<i32, Error> {
+        LeagueTransaction::create_player_with_id(self, player).await
+    }
+
+    async fn update_player(&mut self, player: &LeaguePlayer) -> Result<(), Error> {
+        LeagueTransaction::update_player(self, player).await.map(|_| ())
+    }
+
+    async fn get_game_by_source_gid(&mut self, source_gid: i32) -> Result<Option<LeagueGame>, Error> {
+        LeagueTransaction::get_game_by_source_gid(self, source_gid).await
+    }
+
+    async fn get_legacy_game_by_season_and_table(&mut self, season_num: i32, table_num: i32) -> Result<Option<LeagueGame>, Error> {
+        LeagueTransaction::get_legacy_game_by_season_and_table(self, season_num, table_num).await
+    }
+
+    async fn create_game(&mut self, game: &LeagueGame) -> Result<i32, Error> {
+        LeagueTransaction::create_game(self, game).await
+    }
+
+    async fn update_game(&mut self, game: &LeagueGame) -> Result<(), Error> {
+        LeagueTransaction::update_game(self, game).await.map(|_| ())
+    }
+
+    async fn get_result_by_table_and_player(&mut self, table_id: i32, player_id: i32) -> Result<Option<LeagueResult>, Error> {
+        LeagueTransaction::get_result_by_table_and_player(self, table_id, player_id).await
+    }
+
+    async fn create_result(&mut self, result: &LeagueResult) -> Result<(), Error> {
+        LeagueTransaction::create_result(self, result).await.map(|_| ())
+    }
+
+    async fn update_result(&mut self, result: &LeagueResult) -> Result<(), Error> {
+        LeagueTransaction::update_result(self, result).await.map(|_| ())
+    }
+}
+
+// 内存实现，测试中代替事务记录写入结果
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemoryStore {
+    pub players: Vec<LeaguePlayer>,
+    pub merged: HashMap<i32, i32>,
+    pub games: Vec<LeagueGame>,
+    pub results: Vec<LeagueResult>,
+}
+
+#[cfg(test)]
+impl MemoryStore {
+    pub fn player_name(&self, id: i32) -> Option<&str> {
+        self.players.iter().find(|p| p.id == id).map(|p| p.name.as_str())
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl SyncStore for MemoryStore {
+    async fn list_players(&mut self) -> Result<Vec<LeaguePlayer>, Error> {
+        Ok(self.players.clone())
+    }
+
+    async fn merged_player_ids(&mut self) -> Result<HashMap<i32, i32>, Error> {
+        Ok(self.merged.clone())
+    }
+
+    async fn create_player(&mut self, player: &LeaguePlayer) -> Result<i32, Error> {
+        let id = self.players.iter().map(|p| p.id).max().unwrap_or(0) + 1;
+        self.players.push(LeaguePlayer::new(id, player.name.clone()));
+        Ok(id)
+    }
+
+    async fn create_player_with_id(&mut self, player: &LeaguePlayer) -> Result<i32, Error> {
+        self.players.push(player.clone());
+        Ok(player.id)
+    }
+
+    async fn update_player(&mut self, player: &LeaguePlayer) -> Result<(), Error> {
+        if let Some(existing) = self.players.iter_mut().find(|p| p.id == player.id) {
+            existing.name = player.name.clone();
+        }
+        Ok(())
+    }
+
+    async fn get_game_by_source_gid(&mut self, source_gid: i32) -> Result<Option<LeagueGame>, Error> {
+        Ok(self.games.iter().find(|g| g.source_gid == Some(source_gid)).cloned())
+    }
+
+    async fn get_legacy_game_by_season_and_table(&mut self, season_num: i32, table_num: i32) -> Result<Option<LeagueGame>, Error> {
+        Ok(self
+            .games
+            .iter()
+            .find(|g| g.source_gid.is_none() && g.season_num == season_num && g.table_num == table_num)
+            .cloned())
+    }
+
+    async fn create_game(&mut self, game: &LeagueGame) -> Result<i32, Error> {
+        let id = self.games.len() as i32 + 1;
+        self.games.push(LeagueGame { id, ..game.clone() });
+        Ok(id)
+    }
+
+    async fn update_game(&mut self, game: &LeagueGame) -> Result<(), Error> {
+        if let Some(existing) = self.games.iter_mut().find(|g| g.id == game.id) {
+            *existing = game.clone();
+        }
+        Ok(())
+    }
+
+    async fn get_result_by_table_and_player(&mut self, table_id: i32, player_id: i32) -> Result<Option<LeagueResult>, Error> {
+        Ok(self.results.iter().find(|r| r.table_id == table_id && r.player_id == player_id).cloned())
+    }
+
+    async fn create_result(&mut self, result: &LeagueResult) -> Result<(), Error> {
+        let id = self.results.len() as i32 + 1;
+        self.results.push(LeagueResult { id, ..result.clone() });
+        Ok(())
+    }
+
+    async fn update_result(&mut self, result: &LeagueResult) -> Result<(), Error> {
+        if let Some(existing) = self.results.iter_mut().find(|r| r.id == result.id) {
+            *existing = result.clone();
+        }
+        Ok(())
+    }
+}
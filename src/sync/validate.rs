@@ -0,0 +1,189 @@
+use crate::sync::DataGame;
+use std::collections::HashSet;
+
+// 四家得分之和允许的误差
+const SCORE_SUM_TOLERANCE: f64 = 0.1;
+// total 与 result + uma - penalty 允许的误差
+const TOTAL_TOLERANCE: f64 = 0.01;
+
+// 统一座位写法，去除括号并大写，无法识别时返回 None
+pub fn normalize_seat(seat: &str) -> Option<&'static str> {
+    match seat.trim_matches(|c| c == '[' || c == ']').to_uppercase().as_str() {
+        "E" | "EAST" => Some("E"),
+        "S" | "SOUTH" => Some("S"),
+        "W" | "WEST" => Some("W"),
+        "N" | "NORTH" => Some("N"),
+        _ => None,
+    }
+}
+
+// 写入前校验单场比赛，返回所有不通过的原因，为空表示通过
+pub fn validate_game(game: &DataGame) -> Vec<String> {
+    let mut reasons = Vec::new();
+
+    // 1. 恰好四个不同的座位和玩家
+    if game.results.len() != 4 {
+        reasons.push(format!("成绩条数为 {}，应为 4", game.results.len()));
+    }
+    let mut seats = HashSet::new();
+    let mut players = HashSet::new();
+    for result in &game.results {
+        match normalize_seat(&result.seat) {
+            Some(seat) => {
+                if !seats.insert(seat) {
+                    reasons.push(format!("座位 {} 重复", seat));
+                }
+            }
+            None => reasons.push(format!("座位无法识别: {}", result.seat)),
+        }
+        if !players.insert(result.player) {
+            reasons.push(format!("玩家 {} 重复", result.player));
+        }
+    }
+
+    // 2. 名次在1到4之间
+    for result in &game.results {
+        match result.position {
+            Some(position) if (1..=4).contains(&position) => {}
+            Some(position) => reasons.push(format!("玩家 {} 的名次为 {}，应在1到4之间", result.player, position)),
+            None => reasons.push(format!("玩家 {} 缺少名次", result.player)),
+        }
+    }
+
+    // 3. 四家得分之和约为0
+    let sum: f64 = game.results.iter().map(|r| r.result).sum();
+    if sum.abs() > SCORE_SUM_TOLERANCE {
+        reasons.push(format!("得分之和为 {:.2}，应约为 0", sum));
+    }
+
+    // 4. total == result + uma - penalty
+    for result in &game.results {
+        match (result.uma, result.total) {
+            (Some(uma), Some(total)) => {
+                let expected = result.result + uma - result.penalty.unwrap_or(0.0);
+                if (total - expected).abs() > TOTAL_TOLERANCE {
+                    reasons.push(format!(
+                        "玩家 {} 的 total 为 {}，应为 result + uma - penalty = {:.2}",
+                        result.player, total, expected
+                    ));
+                }
+            }
+            _ => reasons.push(format!("玩家 {} 缺少 uma 或 total", result.player)),
+        }
+    }
+
+    reasons
+}
+
+// 按校验结果拆分上游比赛，已放行的gid直接通过
+pub fn partition<'a>(
+    games: &'a [DataGame],
+    released: &HashSet<i32>,
+) -> (Vec<&'a DataGame>, Vec<(&'a DataGame, Vec<String>)>) {
+    let mut valid = Vec::new();
+    let mut invalid = Vec::new();
+    for game in games {
+        if released.contains(&(game.gid as i32)) {
+            valid.push(game);
+            continue;
+        }
+        let reasons = validate_game(game);
+        if reasons.is_empty() {
+            valid.push(game);
+        } else {
+            invalid.push((game, reasons));
+        }
+    }
+    (valid, invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::DataResult;
+
+    fn result(player: usize, seat: &str, score: f64, position: u8, uma: f64) -> DataResult {
+        DataResult {
+            player,
+            result: score,
+            seat: seat.to_string(),
+            uma: Some(uma),
+            position: Some(position),
+            penalty: Some(0.0),
+            total: Some(score + uma),
+        }
+    }
+
+    fn game() -> DataGame {
+        DataGame {
+            gid: 1,
+            played: "2025-02-12".to_string(),
+            description: "Season 0: Table 1".to_string(),
+            players: vec![1, 2, 3, 4],
+            results: vec![
+                result(1, "[E]", 28.1, 1, 15.0),
+                result(2, "[S]", -25.8, 4, -15.0),
+                result(3, "[W]", 8.9, 2, 5.0),
+                result(4, "[N]", -11.2, 3, -5.0),
+            ],
+            registered: None,
+        }
+    }
+
+    #[test]
+    fn valid_game_passes() {
+        assert!(validate_game(&game()).is_empty());
+    }
+
+    #[test]
+    fn rejects_missing_or_duplicate_seats_and_players() {
+        let mut g = game();
+        g.results.pop();
+        assert!(validate_game(&g).iter().any(|r| r.contains("成绩条数为 3")));
+
+        let mut g = game();
+        g.results[1].seat = "East".to_string();
+        assert_eq!(validate_game(&g), vec!["座位 E 重复"]);
+
+        let mut g = game();
+        g.results[1].player = 1;
+        assert_eq!(validate_game(&g), vec!["玩家 1 重复"]);
+    }
+
+    #[test]
+    fn rejects_positions_outside_one_to_four() {
+        let mut g = game();
+        g.results[0].position = Some(5);
+        assert_eq!(validate_game(&g).len(), 1);
+
+        let mut g = game();
+        g.results[0].position = None;
+        assert_eq!(validate_game(&g), vec!["玩家 1 缺少名次"]);
+    }
+
+    #[test]
+    fn score_sum_tolerance() {
+        let mut g = game();
+        g.results[0].result += 0.05;
+        g.results[0].total = Some(g.results[0].result + 15.0);
+        assert!(validate_game(&g).is_empty());
+
+        g.results[0].result += 0.1;
+        g.results[0].total = Some(g.results[0].result + 15.0);
+        assert_eq!(validate_game(&g), vec!["得分之和为 0.15，应约为 0"]);
+    }
+
+    #[test]
+    fn total_must_match_result_uma_and_penalty() {
+        let mut g = game();
+        g.results[0].total = Some(43.105);
+        assert!(validate_game(&g).is_empty());
+
+        g.results[0].penalty = Some(2.0);
+        assert_eq!(validate_game(&g).len(), 1);
+
+        let mut g = game();
+        g.results[0].uma = None;
+        assert_eq!(validate_game(&g), vec!["玩家 1 缺少 uma 或 total"]);
+    }
+}
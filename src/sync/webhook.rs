@@ -0,0 +1,145 @@
+use crate::config::{WebhookConfig, SYNC_CONFIG};
+use crate::db::LeagueRepository;
+use crate::sync::fetch;
+use crate::sync::SyncSummary;
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+pub const EVENT_SYNC_COMPLETED: &str = "sync.completed";
+
+lazy_static::lazy_static! {
+    // 投递webhook用的HTTP客户端，接收方应尽快返回
+    static ref CLIENT: reqwest::Client = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(SYNC_CONFIG.fetch.connect_timeout_secs))
+        .timeout(Duration::from_secs(10))
+        .build()
+        .expect("创建HTTP客户端失败");
+}
+
+// 请求体的 HMAC-SHA256 签名，接收方用同一密钥计算后比对
+pub fn signature(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC接受任意长度的密钥");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+// 同步成功写入后通知所有配置的webhook，投递在后台进行，不影响同步结果
+// 返回各投递任务，进程马上要退出时（命令行导入）需等待它们结束
+pub fn dispatch(repo: &LeagueRepository, source: &str, summary: &SyncSummary) -> Vec<JoinHandle<()>> {
+    if SYNC_CONFIG.webhooks.is_empty() {
+        return Vec::new();
+    }
+    let payload = build_payload(source, summary);
+    SYNC_CONFIG
+        .webhooks
+        .iter()
+        .map(|hook| tokio::spawn(deliver(repo.clone(), hook.clone(), summary.run_id, payload.clone())))
+        .collect()
+}
+
+fn build_payload(source: &str, summary: &SyncSummary) -> Value {
+    let changes = summary.changes.as_ref();
+    json!({
+        "event": EVENT_SYNC_COMPLETED,
+        "run_id": summary.run_id,
+        "source": source,
+        "snapshot_id": summary.snapshot_id,
+        "summary": {
+            "success_count": summary.success_count,
+            "saved_count": summary.saved_count,
+            "updated_games_count": summary.updated_games_count,
+            "results_written_count": summary.results_written_count,
+            "created_players_count": summary.created_players_count,
+            "updated_players_count": summary.updated_players_count,
+            "sessions_written_count": summary.sessions_written_count,
+            "orphan_tables_count": summary.orphan_tables_count,
+            "orphan_results_count": summary.orphan_results_count,
+            "quarantined_count": summary.quarantined_count,
+            "warnings": summary.warnings,
+            "message": summary.message(),
+        },
+        "tables_created": changes.map(|c| &c.tables_created),
+        "results_changed": changes.map(|c| &c.results_changed),
+        "players_created": changes.map(|c| &c.players_created),
+        "players_renamed": changes.map(|c| &c.players_renamed),
+    })
+}
+
+// 登记投递记录后发送，失败按拉取的退避策略重试，每次尝试的结果写回记录
+async fn deliver(repo: LeagueRepository, hook: WebhookConfig, run_id: i32, payload: Value) {
+    let delivery_id = match repo
+        .create_webhook_delivery(&hook.name, &hook.url, EVENT_SYNC_COMPLETED, run_id, &payload)
+        .await
+    {
+        Ok(id) => id,
+        Err(e) => {
+            println!("❌ 登记webhook {} 的投递记录失败，放弃投递: {}", hook.name, e);
+            return;
+        }
+    };
+    let body = serde_json::to_vec(&payload).unwrap_or_default();
+
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let (response_status, error) = match send(&hook, delivery_id, &body).await {
+            Ok(status) if status.is_success() => (Some(status.as_u16() as i32), None),
+            Ok(status) => (Some(status.as_u16() as i32), Some(format!("HTTP {}", status))),
+            Err(e) => (None, Some(e)),
+        };
+        let retry = error.is_some() && attempts <= hook.retries;
+        let status = match (&error, retry) {
+            (None, _) => "delivered",
+            (Some(_), true) => "pending",
+            (Some(_), false) => "failed",
+        };
+        if let Err(e) = repo
+            .record_webhook_attempt(delivery_id, status, attempts as i32, response_status, error.as_deref())
+            .await
+        {
+            println!("❌ 更新webhook投递记录 {} 失败: {}", delivery_id, e);
+        }
+
+        match error {
+            None => {
+                println!("📨 webhook {} 投递成功（投递记录 {}，运行记录 {}）", hook.name, delivery_id, run_id);
+                return;
+            }
+            Some(e) if retry => {
+                let delay = fetch::backoff(attempts - 1);
+                println!(
+                    "⚠️  webhook {} 投递失败: {}，{} 毫秒后第 {} 次重试",
+                    hook.name, e, delay.as_millis(), attempts
+                );
+                tokio::time::sleep(delay).await;
+            }
+            Some(e) => {
+                println!(
+                    "❌ webhook {} 投递失败（投递记录 {}，共尝试 {} 次）: {}",
+                    hook.name, delivery_id, attempts, e
+                );
+                return;
+            }
+        }
+    }
+}
+
+async fn send(hook: &WebhookConfig, delivery_id: i32, body: &[u8]) -> Result<reqwest::StatusCode, String> {
+    let mut request = CLIENT
+        .post(&hook.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header("X-Ankan-Event", EVENT_SYNC_COMPLETED)
+        .header("X-Ankan-Delivery", delivery_id.to_string());
+    if let Some(secret) = &hook.secret {
+        request = request.header("X-Ankan-Signature-256", signature(secret, body));
+    }
+    request
+        .body(body.to_vec())
+        .send()
+        .await
+        .map(|resp| resp.status())
+        .map_err(|e| fetch::network_error(&format!("webhook {}", hook.name), e).message)
+}